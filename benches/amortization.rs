@@ -19,14 +19,15 @@ pub(crate) fn commit_and_open<R: Rng>(
     let poly = [0; 2_usize.pow(SIZE as u32)]
         .map(|_| Fr::rand(&mut rng))
         .to_vec();
-    let commit = scheme.commit(poly.clone());
+    let commit = scheme.commit(poly.clone()).unwrap();
     let point = Fr::from(5);
     let eval = {
-        let poly = Poly::from_coefficients_slice(&*poly);
+        let poly = Poly::from_coefficients_slice(&poly);
         poly.evaluate(&point)
     };
     (commit, poly, point, eval)
 }
+#[allow(clippy::type_complexity)]
 fn sample<R: Rng>(
     scheme: &IpaScheme<PallasParameters, R>,
     size: usize,
@@ -37,8 +38,8 @@ fn sample<R: Rng>(
 ) {
     let (commitments, opens) = (0..=size)
         .map(|_| {
-            let a = commit_and_open(&scheme);
-            (a.0.clone(), a)
+            let a = commit_and_open(scheme);
+            (a.0, a)
         })
         .unzip::<_, _, Vec<_>, Vec<_>>();
     let (polys, opens) = opens
@@ -54,7 +55,8 @@ fn sample<R: Rng>(
 pub fn batch_verify(c: &mut Criterion) {
     let mut group = c.benchmark_group("batch_verify");
     let scheme =
-        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), SIZE as u8, false, thread_rng());
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), SIZE as u8, false, thread_rng())
+            .unwrap();
 
     let (polys, commitments, opens) = sample(&scheme, 2_usize.pow(MAX as u32));
     for size in 0..=MAX {
@@ -70,14 +72,14 @@ pub fn batch_verify(c: &mut Criterion) {
                 (commit, &**poly, point, eval)
             })
             .collect::<Vec<_>>();
-        let multi_open = scheme.batch_open(opens);
+        let multi_open = scheme.batch_open(opens).unwrap();
         let commitments = commitments[0..max].to_vec();
 
         group.bench_with_input(BenchmarkId::from_parameter(max), &size, |b, _size| {
             b.iter_batched(
                 || (multi_open.clone(), commitments.clone()),
                 |(multi_open, commitments)| {
-                    let evals = scheme.batch_verify(&*commitments, multi_open);
+                    let evals = scheme.batch_verify(&commitments, multi_open);
                     (evals, commitments)
                 },
                 criterion::BatchSize::SmallInput,
@@ -12,7 +12,7 @@ type Fr = <GroupAffine<PallasParameters> as AffineCurve>::ScalarField;
 const RANGE: RangeInclusive<u8> = 8..=14;
 
 fn sample(size: u8) -> (Scheme, Vec<Fr>, ThreadRng) {
-    let scheme = Scheme::init(Init::Seed(1), size, false, thread_rng());
+    let scheme = Scheme::init(Init::Seed(1), size, false, thread_rng()).unwrap();
     let mut rng = thread_rng();
     //let poly: [Fr<PallasParameters>; 2_usize.pow(SIZE as u32)] = rng.gen();
     let poly: Vec<Fr> = repeat(())
@@ -31,7 +31,8 @@ pub fn commit(c: &mut Criterion) {
 
             let coeffs = black_box(poly.to_vec());
             b.iter(|| {
-                let _commit: Commitment<_, false> = scheme.commit(black_box(coeffs.clone()));
+                let _commit: Commitment<_, false> =
+                    scheme.commit(black_box(coeffs.clone())).unwrap();
             })
         });
     }
@@ -46,12 +47,12 @@ pub fn open(c: &mut Criterion) {
             b.iter_batched(
                 || {
                     let coeffs = black_box(poly.to_vec());
-                    let commit = scheme.commit(coeffs.clone());
+                    let commit = scheme.commit(coeffs.clone()).unwrap();
                     let point: Fr = rng.gen();
                     let (eval, poly) = {
                         let poly =
                             ark_poly::univariate::DensePolynomial::<Fr>::from_coefficients_slice(
-                                &*coeffs,
+                                &coeffs,
                             );
                         let eval = poly.evaluate(&point);
                         (eval, poly.coeffs)
@@ -59,7 +60,7 @@ pub fn open(c: &mut Criterion) {
                     (point, commit, poly, eval)
                 },
                 |(point, commitment, a, eval)| {
-                    let open: Opening<_> = scheme.open(commitment, &*a, point, eval);
+                    let open: Opening<_> = scheme.open(commitment, &a, point, eval).unwrap();
                     (open, a)
                 },
                 criterion::BatchSize::SmallInput,
@@ -77,33 +78,88 @@ pub fn verify(c: &mut Criterion) {
             b.iter_batched(
                 || {
                     let coeffs = black_box(poly.to_vec());
-                    let commit = scheme.commit(coeffs.clone());
+                    let commit = scheme.commit(coeffs.clone()).unwrap();
                     let point: Fr = rng.gen();
                     let (eval, poly) = {
                         let poly =
                             ark_poly::univariate::DensePolynomial::<Fr>::from_coefficients_slice(
-                                &*coeffs,
+                                &coeffs,
                             );
                         let eval = poly.evaluate(&point);
                         (eval, poly.coeffs)
                     };
                     //(point, commit, poly, eval)
-                    let open: Opening<_> = scheme.open(commit, &*poly, point, eval);
+                    let open: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
                     (commit, open)
                 },
-                |(commit, open)| {
-                    let open = scheme.verify(commit, open);
-                    open
-                },
+                |(commit, open)| scheme.verify(commit, open),
                 criterion::BatchSize::SmallInput,
             );
         });
     }
     group.finish();
 }
+///with the `parallel` feature, compares one thread against all of them at the
+///largest size
+#[cfg(feature = "parallel")]
+pub fn threads(c: &mut Criterion) {
+    use itertools::Itertools;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let size = *RANGE.end();
+    let scheme = IpaScheme::<PallasParameters, _>::init(
+        Init::Seed(1),
+        size,
+        false,
+        StdRng::seed_from_u64(1),
+    )
+    .unwrap();
+    let (_, poly, mut rng) = sample(size);
+    let point: Fr = rng.gen();
+    let eval = ark_poly::univariate::DensePolynomial::<Fr>::from_coefficients_slice(&poly)
+        .evaluate(&point);
+    let commit: Commitment<_, false> = scheme.commit(poly.clone()).unwrap();
+    let open: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+
+    let mut group = c.benchmark_group("threads");
+    for threads in [1, rayon::current_num_threads()].into_iter().dedup() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(BenchmarkId::new("commit", threads), &threads, |b, _| {
+            b.iter(|| {
+                pool.install(|| {
+                    let _commit: Commitment<_, false> =
+                        scheme.commit(black_box(poly.clone())).unwrap();
+                })
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("open", threads), &threads, |b, _| {
+            b.iter(|| {
+                pool.install(|| {
+                    let _open: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+                })
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("verify", threads), &threads, |b, _| {
+            b.iter(|| pool.install(|| scheme.verify(commit, black_box(open.clone()))))
+        });
+    }
+    group.finish();
+}
 criterion_group!(
     name = benches;
     config = Criterion::default().sample_size(10);
     targets = commit, open, verify
 );
+#[cfg(feature = "parallel")]
+criterion_group!(
+    name = parallel;
+    config = Criterion::default().sample_size(10);
+    targets = threads
+);
+#[cfg(not(feature = "parallel"))]
 criterion_main!(benches);
+#[cfg(feature = "parallel")]
+criterion_main!(benches, parallel);
@@ -12,7 +12,7 @@ type Fr = <GroupAffine<PallasParameters> as AffineCurve>::ScalarField;
 const SIZE: u8 = 10;
 
 fn sample(size: u8) -> (Scheme, Vec<Fr>, ThreadRng) {
-    let scheme = Scheme::init(Init::Seed(1), size, false, thread_rng());
+    let scheme = Scheme::init(Init::Seed(1), size, false, thread_rng()).unwrap();
     let mut rng = thread_rng();
     let poly: Vec<Fr> = repeat(())
         .map(|_| rng.gen())
@@ -27,7 +27,7 @@ pub fn commit(c: &mut Criterion) {
 
         let coeffs = black_box(poly.to_vec());
         b.iter(|| {
-            let _commit: Commitment<_, false> = scheme.commit(black_box(coeffs.clone()));
+            let _commit: Commitment<_, false> = scheme.commit(black_box(coeffs.clone())).unwrap();
         })
     });
 }
@@ -37,11 +37,11 @@ pub fn open(c: &mut Criterion) {
         b.iter_batched(
             || {
                 let coeffs = black_box(poly.to_vec());
-                let commit = scheme.commit(coeffs.clone());
+                let commit = scheme.commit(coeffs.clone()).unwrap();
                 let point: Fr = rng.gen();
                 let (eval, poly) = {
                     let poly = ark_poly::univariate::DensePolynomial::<Fr>::from_coefficients_slice(
-                        &*coeffs,
+                        &coeffs,
                     );
                     let eval = poly.evaluate(&point);
                     (eval, poly.coeffs)
@@ -49,7 +49,7 @@ pub fn open(c: &mut Criterion) {
                 (point, commit, poly, eval)
             },
             |(point, commitment, a, eval)| {
-                let open: Opening<_> = scheme.open(commitment, &*a, point, eval);
+                let open: Opening<_> = scheme.open(commitment, &a, point, eval).unwrap();
                 (open, a)
             },
             criterion::BatchSize::SmallInput,
@@ -62,23 +62,20 @@ pub fn verify(c: &mut Criterion) {
         b.iter_batched(
             || {
                 let coeffs = black_box(poly.to_vec());
-                let commit = scheme.commit(coeffs.clone());
+                let commit = scheme.commit(coeffs.clone()).unwrap();
                 let point: Fr = rng.gen();
                 let (eval, poly) = {
                     let poly = ark_poly::univariate::DensePolynomial::<Fr>::from_coefficients_slice(
-                        &*coeffs,
+                        &coeffs,
                     );
                     let eval = poly.evaluate(&point);
                     (eval, poly.coeffs)
                 };
                 //(point, commit, poly, eval)
-                let open: Opening<_> = scheme.open(commit, &*poly, point, eval);
+                let open: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
                 (commit, open)
             },
-            |(commit, open)| {
-                let open = scheme.verify(commit, open);
-                open
-            },
+            |(commit, open)| scheme.verify(commit, open),
             criterion::BatchSize::SmallInput,
         );
     });
@@ -7,7 +7,8 @@ use std::iter::repeat;
 type Fr<P> = <GroupAffine<P> as AffineCurve>::ScalarField;
 pub fn commit_iai() {
     const SIZE: u8 = 10;
-    let scheme = IpaScheme::<PallasParameters, _>::init(Init::Seed(1), SIZE, false, thread_rng());
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), SIZE, false, thread_rng()).unwrap();
     let mut rng = thread_rng();
     //let poly: [Fr<PallasParameters>; 2_usize.pow(SIZE as u32)] = rng.gen();
     let poly: Vec<Fr<PallasParameters>> = repeat(())
@@ -16,6 +17,6 @@ pub fn commit_iai() {
         .collect();
     //panic!();
     let coeffs = iai::black_box(poly.to_vec());
-    let _: Commitment<_, false> = scheme.commit(coeffs.clone());
+    let _: Commitment<_, false> = scheme.commit(coeffs.clone()).unwrap();
 }
 iai::main!(commit_iai);
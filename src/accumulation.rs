@@ -0,0 +1,405 @@
+use crate::{
+    challenges::ChallengeGenerator,
+    open::{open_with_basis, OpenTrait, VerifTrait},
+    transcript::Transcript,
+    utils::{cfg_iter, eval_b_poly, s_vector},
+    Commitment, Fr, IpaError, IpaScheme, Opening, VerifierKey,
+};
+use ark_ec::{
+    short_weierstrass_jacobian::{GroupAffine, GroupProjective},
+    AffineCurve, ModelParameters, ProjectiveCurve, SWModelParameters,
+};
+use ark_ff::{Field, One, Zero};
+use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+use rand::Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::iter::successors;
+
+///an opening with the final generator it reduces to, so it can be checked in
+///logarithmic time leaving the generator for `decide`
+#[derive(Debug, Clone)]
+pub struct DeferredOpening<P: SWModelParameters> {
+    pub(crate) opening: Opening<P>,
+    pub(crate) final_basis: GroupAffine<P>,
+}
+
+///what is left to check of an opening: the final generator must be the
+///commitment to the s-vector of the challenges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accumulator<P: SWModelParameters> {
+    pub(crate) challenges: Vec<Fr<P>>,
+    pub(crate) final_basis: GroupAffine<P>,
+}
+
+impl<P: SWModelParameters> Accumulator<P> {
+    ///challenges of the rounds, in order
+    pub fn challenges(&self) -> &[Fr<P>] {
+        &self.challenges
+    }
+    pub fn final_basis(&self) -> GroupAffine<P> {
+        self.final_basis
+    }
+    ///challenges paired with their inverses
+    #[allow(clippy::type_complexity)]
+    fn challenge_pairs(&self) -> Result<Vec<(Fr<P>, Fr<P>)>, IpaError> {
+        self.challenges
+            .iter()
+            .map(|challenge| {
+                let inverse = challenge.inverse().ok_or(IpaError::ZeroChallenge)?;
+                Ok((*challenge, inverse))
+            })
+            .collect()
+    }
+}
+
+///proof that an accumulator combines some accumulators and openings: the
+///final generators of the openings and the opening of the combination
+#[derive(Debug, Clone)]
+pub struct AccumulationProof<P: SWModelParameters> {
+    pub(crate) final_bases: Vec<GroupAffine<P>>,
+    pub(crate) opening: DeferredOpening<P>,
+}
+
+impl<P, R, T> OpenTrait<P, R, T> for DeferredOpening<P>
+where
+    P: SWModelParameters,
+    R: Rng,
+    T: Transcript<P>,
+{
+    type Commit = Commitment<P, false>;
+
+    fn open(
+        scheme: &IpaScheme<P, R, T>,
+        commitment: Self::Commit,
+        coeffs: &[Fr<P>],
+        point: Fr<P>,
+        eval: Fr<P>,
+    ) -> Result<Self, IpaError> {
+        let (opening, final_basis) = open_with_basis(scheme, commitment, coeffs, point, eval)?;
+        Ok(Self {
+            opening,
+            final_basis,
+        })
+    }
+}
+
+///the succinct check followed by the decision of its accumulator
+impl<P, T> VerifTrait<P, T> for DeferredOpening<P>
+where
+    P: SWModelParameters,
+    T: Transcript<P>,
+{
+    type Commit = Commitment<P, false>;
+
+    fn verify(self, key: &VerifierKey<P, T>, commitment: Self::Commit) -> Result<Fr<P>, IpaError> {
+        let eval = self.opening.eval;
+        let accumulator = key.verify_succinct(commitment, self)?;
+        key.decide(&[accumulator])?;
+        Ok(eval)
+    }
+}
+
+impl<P, R, T> IpaScheme<P, R, T>
+where
+    P: ModelParameters + SWModelParameters,
+    Fr<P>: One,
+    R: Rng,
+    T: Transcript<P>,
+{
+    ///accumulates the openings into the accumulators, the openings are
+    ///checked and the s-polynomials of all of them are combined and opened at a
+    ///challenge point. The new accumulator is left for `decide`
+    pub fn accumulate(
+        &self,
+        accumulators: &[Accumulator<P>],
+        openings: &[(Commitment<P, false>, Opening<P>)],
+    ) -> Result<(Accumulator<P>, AccumulationProof<P>), IpaError> {
+        let vk = &self.vk;
+        let succinct = cfg_iter!(openings)
+            .map(|(commitment, opening)| vk.succinct_check(commitment, opening, None))
+            .collect::<Result<Vec<_>, IpaError>>()?;
+        let final_bases = succinct.iter().map(Accumulator::final_basis).collect();
+        let (combination, point) = vk.accumulation_challenges(accumulators, &succinct)?;
+        let (s, commitment) = vk.combine(accumulators.iter().chain(&succinct), combination)?;
+        let s = DensePolynomial::from_coefficients_vec(s);
+        let eval = s.evaluate(&point);
+        let commitment = commitment.into_affine();
+        let opening: DeferredOpening<P> =
+            self.open(Commitment(commitment), &s.coeffs, point, eval)?;
+        let accumulator = vk.succinct_check(
+            &Commitment(commitment),
+            &opening.opening,
+            Some(opening.final_basis),
+        )?;
+        let proof = AccumulationProof {
+            final_bases,
+            opening,
+        };
+        Ok((accumulator, proof))
+    }
+}
+
+impl<P, T> VerifierKey<P, T>
+where
+    P: ModelParameters + SWModelParameters,
+    Fr<P>: One,
+    T: Transcript<P>,
+{
+    ///checks the opening with the claimed final generator in O(log n), the
+    ///opening is only valid once its accumulator passes `decide`
+    pub fn verify_succinct(
+        &self,
+        commitment: Commitment<P, false>,
+        opening: DeferredOpening<P>,
+    ) -> Result<Accumulator<P>, IpaError> {
+        self.succinct_check(&commitment, &opening.opening, Some(opening.final_basis))
+    }
+    ///without a claimed final generator it is computed from the challenges, as
+    ///the prover does when accumulating
+    fn succinct_check(
+        &self,
+        commitment: &Commitment<P, false>,
+        opening: &Opening<P>,
+        final_basis: Option<GroupAffine<P>>,
+    ) -> Result<Accumulator<P>, IpaError> {
+        let Opening {
+            point,
+            eval,
+            rounds,
+            a,
+        } = opening;
+        self.check_round_count(rounds.len())?;
+        let mut transcript = ChallengeGenerator::<P, T>::new_opening(commitment, point, eval);
+        let u = transcript.inner_product_basis();
+        let (final_commit, challenges) =
+            Self::process_rounds(commitment, *eval, rounds, u, &mut transcript)?;
+        let final_basis =
+            final_basis.unwrap_or_else(|| self.s_to_basis(s_vector::<P>(&challenges)));
+        let b = eval_b_poly::<P>(&challenges, *point);
+        if final_commit != final_basis.mul(*a) + u.mul(*a * b) {
+            return Err(IpaError::FinalCheckFailed);
+        }
+        let challenges = challenges
+            .into_iter()
+            .map(|(challenge, _)| challenge)
+            .collect();
+        Ok(Accumulator {
+            challenges,
+            final_basis,
+        })
+    }
+    ///checks that the accumulator is the accumulation of the accumulators and
+    ///the openings in O(log n) for each of them, the openings are only valid
+    ///once the returned accumulator passes `decide`
+    pub fn verify_accumulation(
+        &self,
+        accumulators: &[Accumulator<P>],
+        openings: &[(Commitment<P, false>, Opening<P>)],
+        proof: AccumulationProof<P>,
+    ) -> Result<Accumulator<P>, IpaError> {
+        let AccumulationProof {
+            final_bases,
+            opening,
+        } = proof;
+        let (succinct, combination, point) =
+            self.accumulation_inputs(accumulators, openings, &final_bases)?;
+        let combinations = successors(Some(Fr::<P>::one()), |power| Some(*power * combination));
+
+        let mut eval = Fr::<P>::zero();
+        let mut commitment = GroupProjective::<P>::zero();
+        for (accumulator, combination) in accumulators.iter().chain(&succinct).zip(combinations) {
+            eval += eval_b_poly::<P>(&accumulator.challenge_pairs()?, point) * combination;
+            commitment += accumulator.final_basis.mul(combination);
+        }
+        if opening.opening.point != point || opening.opening.eval != eval {
+            return Err(IpaError::AccumulationMismatch);
+        }
+        self.verify_succinct(Commitment(commitment.into_affine()), opening)
+    }
+    ///succinct checks of the openings with their final generators, and the
+    ///combination element and point derived from all the accumulators
+    #[allow(clippy::type_complexity)]
+    fn accumulation_inputs(
+        &self,
+        accumulators: &[Accumulator<P>],
+        openings: &[(Commitment<P, false>, Opening<P>)],
+        final_bases: &[GroupAffine<P>],
+    ) -> Result<(Vec<Accumulator<P>>, Fr<P>, Fr<P>), IpaError> {
+        if accumulators.is_empty() && openings.is_empty() {
+            return Err(IpaError::EmptyBatch);
+        }
+        if openings.len() != final_bases.len() {
+            return Err(IpaError::LengthMismatch {
+                expected: openings.len(),
+                found: final_bases.len(),
+            });
+        }
+        let succinct = cfg_iter!(openings)
+            .zip(final_bases)
+            .map(|((commitment, opening), final_basis)| {
+                self.succinct_check(commitment, opening, Some(*final_basis))
+            })
+            .collect::<Result<Vec<_>, IpaError>>()?;
+        let (combination, point) = self.accumulation_challenges(accumulators, &succinct)?;
+        Ok((succinct, combination, point))
+    }
+    ///the combination element and point derived from all the accumulators,
+    ///including the ones of the openings
+    fn accumulation_challenges(
+        &self,
+        accumulators: &[Accumulator<P>],
+        succinct: &[Accumulator<P>],
+    ) -> Result<(Fr<P>, Fr<P>), IpaError> {
+        if accumulators.is_empty() && succinct.is_empty() {
+            return Err(IpaError::EmptyBatch);
+        }
+        let mut generator = ChallengeGenerator::<P, T>::new_accumulation();
+        for accumulator in accumulators.iter().chain(succinct) {
+            self.check_round_count(accumulator.challenges.len())?;
+            generator.digest_accumulator(&accumulator.challenges, &accumulator.final_basis);
+        }
+        Ok(generator.accumulation_elements())
+    }
+    ///checks the final generators of all the accumulators with a single MSM,
+    ///combining them with the powers of a challenge derived from all of them
+    pub fn decide(&self, accumulators: &[Accumulator<P>]) -> Result<(), IpaError> {
+        if accumulators.is_empty() {
+            return Err(IpaError::EmptyBatch);
+        }
+        let mut generator = ChallengeGenerator::<P, T>::new_decision();
+        for accumulator in accumulators {
+            self.check_round_count(accumulator.challenges.len())?;
+            generator.digest_accumulator(&accumulator.challenges, &accumulator.final_basis);
+        }
+        let element = generator.decision_element();
+        let (s, combined_basis) = self.combine(accumulators.iter(), element)?;
+        match self.s_to_basis(s) == combined_basis.into_affine() {
+            true => Ok(()),
+            false => Err(IpaError::FinalCheckFailed),
+        }
+    }
+    ///the s-vectors and the final generators of the accumulators combined with
+    ///the powers of the element
+    fn combine<'a>(
+        &self,
+        accumulators: impl Iterator<Item = &'a Accumulator<P>>,
+        element: Fr<P>,
+    ) -> Result<(Vec<Fr<P>>, GroupProjective<P>), IpaError> {
+        let combinations = successors(Some(Fr::<P>::one()), |power| Some(*power * element));
+        let mut s = vec![Fr::<P>::zero(); self.max_degree];
+        let mut combined_basis = GroupProjective::<P>::zero();
+        for (accumulator, combination) in accumulators.zip(combinations) {
+            let challenges = accumulator.challenge_pairs()?;
+            for (s, coeff) in s.iter_mut().zip(s_vector::<P>(&challenges)) {
+                *s += coeff * combination;
+            }
+            combined_basis += accumulator.final_basis.mul(combination);
+        }
+        Ok((s, combined_basis))
+    }
+}
+
+#[test]
+fn accumulation() {
+    use crate::{tests::commit_and_open, Init};
+    use ark_pallas::PallasParameters;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use rand::thread_rng;
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, false, thread_rng()).unwrap();
+    let key = scheme.verifier_key();
+    let accumulators = (0..4)
+        .map(|_| {
+            let (commit, poly, point, eval) = commit_and_open(&scheme);
+            let opening: DeferredOpening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+            key.verify_succinct(commit, opening).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert!(key.decide(&accumulators).is_ok());
+    assert!(matches!(key.decide(&[]), Err(IpaError::EmptyBatch)));
+
+    let mut bytes = vec![];
+    accumulators[0].serialize(&mut bytes).unwrap();
+    let accumulator = Accumulator::deserialize(&*bytes).unwrap();
+    assert!(accumulator == accumulators[0]);
+
+    //a generator that is not the commitment to the s-vector must be rejected
+    let mut wrong = accumulators.clone();
+    wrong[2].final_basis = accumulators[1].final_basis;
+    assert!(matches!(
+        key.decide(&wrong),
+        Err(IpaError::FinalCheckFailed)
+    ));
+    let mut wrong = accumulators;
+    wrong[0].challenges.swap(0, 1);
+    assert!(key.decide(&wrong).is_err());
+
+    let (commit, poly, point, eval) = commit_and_open(&scheme);
+    let opening: DeferredOpening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    assert_eq!(scheme.verify(commit, opening.clone()).unwrap(), eval);
+    let mut tampered = opening;
+    tampered.final_basis = scheme.vk.basis[0];
+    assert!(key.verify_succinct(commit, tampered).is_err());
+}
+
+#[test]
+fn accumulation_steps() {
+    use crate::{tests::commit_and_open, Init};
+    use ark_pallas::PallasParameters;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use rand::thread_rng;
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, false, thread_rng()).unwrap();
+    let key = scheme.verifier_key();
+    let openings = || {
+        (0..2)
+            .map(|_| {
+                let (commit, poly, point, eval) = commit_and_open(&scheme);
+                let opening: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+                (commit, opening)
+            })
+            .collect::<Vec<_>>()
+    };
+    assert!(matches!(
+        scheme.accumulate(&[], &[]),
+        Err(IpaError::EmptyBatch)
+    ));
+
+    let first = openings();
+    let (accumulator, proof) = scheme.accumulate(&[], &first).unwrap();
+    let verified = key.verify_accumulation(&[], &first, proof).unwrap();
+    assert!(verified == accumulator);
+
+    let second = openings();
+    let previous = [accumulator];
+    let (accumulator, proof) = scheme.accumulate(&previous, &second).unwrap();
+    let mut bytes = vec![];
+    proof.serialize(&mut bytes).unwrap();
+    let proof = AccumulationProof::deserialize(&*bytes).unwrap();
+    let verified = key
+        .verify_accumulation(&previous, &second, proof.clone())
+        .unwrap();
+    assert!(verified == accumulator);
+    assert!(key.decide(&[accumulator]).is_ok());
+
+    //the proof only holds for the accumulated claims
+    assert!(key
+        .verify_accumulation(&previous, &first, proof.clone())
+        .is_err());
+    assert!(key
+        .verify_accumulation(&[], &second, proof.clone())
+        .is_err());
+    let mut wrong = proof;
+    wrong.final_bases.swap(0, 1);
+    assert!(key.verify_accumulation(&previous, &second, wrong).is_err());
+
+    //an accumulator with a wrong final generator can't be decided nor
+    //accumulated further
+    let mut bad = previous[0].clone();
+    bad.final_basis = scheme.vk.basis[0];
+    assert!(key.decide(&[bad.clone()]).is_err());
+    assert!(scheme.accumulate(&[bad], &second).is_err());
+}
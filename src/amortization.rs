@@ -1,62 +1,153 @@
-use crate::{challenges::ChallengeGenerator, Assert, Commitment, Fr, IpaScheme, IsFalse, Opening};
+use crate::{
+    challenges::ChallengeGenerator,
+    open::AmortizedVerifTrait,
+    transcript::Transcript,
+    utils::{cfg_into_iter, eval_b_poly, s_vector},
+    Assert, Commitment, Fr, HidingOpening, IpaError, IpaScheme, IsFalse, Opening,
+    UnsafeHidingCommitment, VerifierKey,
+};
 use ark_ec::{
     short_weierstrass_jacobian::{GroupAffine, GroupProjective},
     AffineCurve, ModelParameters, ProjectiveCurve, SWModelParameters,
 };
 use ark_ff::{One, Zero};
-use ark_poly::{
-    univariate::{DenseOrSparsePolynomial, DensePolynomial, SparsePolynomial},
-    Polynomial, UVPolynomial,
-};
-use rand::Rng;
-use std::{iter::successors, ops::Mul};
+use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::iter::successors;
 
+///individual openings checked against their claimed final basis, and one
+///opening proving all those basis at once. With `HidingOpening`s each opening
+///keeps its own blinding, the batch opening is of the public s-polynomials
 #[derive(Clone)]
-pub struct MultiOpening<P: SWModelParameters> {
-    openings: Vec<(Opening<P>, GroupAffine<P>)>,
-    batch_opening: Opening<P>,
+pub struct MultiOpening<P: SWModelParameters, O = Opening<P>> {
+    pub(crate) openings: Vec<(O, GroupAffine<P>)>,
+    pub(crate) batch_opening: Opening<P>,
+}
+
+///openings of several polynomials at the same point, as a single opening of
+///their random lineal combination
+#[derive(Debug, Clone)]
+pub struct PointOpening<P: SWModelParameters> {
+    pub(crate) evals: Vec<Fr<P>>,
+    pub(crate) opening: Opening<P>,
 }
 
-impl<P, R> IpaScheme<P, R>
+impl<P: SWModelParameters> PointOpening<P> {
+    ///claimed evaluations, in the order of the commitments
+    pub fn evals(&self) -> &[Fr<P>] {
+        &self.evals
+    }
+}
+
+impl<P, R, T> IpaScheme<P, R, T>
 where
     P: ModelParameters + SWModelParameters,
     Fr<P>: One,
     R: Rng,
+    T: Transcript<P>,
 {
+    #[allow(clippy::type_complexity)]
     pub fn batch_open<const HIDING: bool>(
         &self,
         opens: Vec<(Commitment<P, HIDING>, &[Fr<P>], Fr<P>, Fr<P>)>,
-    ) -> MultiOpening<P>
+    ) -> Result<MultiOpening<P>, IpaError>
     where
         Assert<HIDING>: IsFalse,
     {
-        let len = opens.len();
-        let (openings, amortization, challenges) = opens
+        if opens.is_empty() {
+            return Err(IpaError::EmptyBatch);
+        }
+        let opens = opens
             .into_iter()
-            .map(|open| {
-                let (commitment, a, point, eval) = open;
-                let u = ChallengeGenerator::inner_product_basis(&commitment, &point);
-                let basis = &*self.basis;
+            .map(|(commitment, a, point, eval)| {
                 let b = self.b(point);
-                let first = Self::round(basis, a, &*b, u, Some(vec![]));
+                let a = self.prepare_opening(a, &b, eval)?;
+                Ok((commitment, a, b, point, eval))
+            })
+            .collect::<Result<Vec<_>, IpaError>>()?;
+        let basis = &*self.vk.basis;
+        let opens = cfg_into_iter!(opens)
+            .map(|(commitment, a, b, point, eval)| {
+                let mut transcript =
+                    ChallengeGenerator::<P, T>::new_opening(&commitment, &point, &eval);
+                let u = transcript.inner_product_basis();
+                let first = Self::round(
+                    basis,
+                    Fr::<P>::one(),
+                    &a,
+                    &b,
+                    u,
+                    Some(vec![]),
+                    &mut transcript,
+                )?;
                 let rounds = vec![(first.lj, first.rj)];
-                let open = Self::open_recursive(first, rounds, point, eval, u);
-                open
+                let (opening, challenges, basis) =
+                    Self::open_recursive(first, rounds, point, eval, u, &mut transcript)?;
+                Ok((Commitment(commitment.0), opening, challenges, basis))
             })
-            .fold(
-                (
-                    Vec::with_capacity(len),
-                    Vec::with_capacity(len),
-                    ChallengeGenerator::new(),
-                ),
-                |mut state, open| {
-                    let (open, s_challenges, basis) = open;
-                    state.0.push(open);
-                    state.1.push((s_challenges, basis));
-                    state.2.digest_for_amortization(basis);
-                    state
-                },
-            );
+            .collect::<Result<Vec<_>, IpaError>>()?;
+        self.amortize(opens)
+    }
+    ///like `batch_open` with zero-knowledge openings, the blinding factor of
+    ///each commitment is consumed by its own opening
+    #[allow(clippy::type_complexity)]
+    pub fn batch_open_hiding(
+        &self,
+        opens: Vec<(UnsafeHidingCommitment<P>, &[Fr<P>], Fr<P>, Fr<P>)>,
+    ) -> Result<MultiOpening<P, HidingOpening<P>>, IpaError> {
+        if opens.is_empty() {
+            return Err(IpaError::EmptyBatch);
+        }
+        //every opening gets its own rng, so the lock isn't held while opening
+        let opens = opens
+            .into_iter()
+            .map(|(commitment, a, point, eval)| {
+                let b = self.b(point);
+                let a = self.prepare_opening(a, &b, eval)?;
+                let rng = StdRng::from_seed(self.lock_rng().gen());
+                Ok((commitment, a, b, point, eval, rng))
+            })
+            .collect::<Result<Vec<_>, IpaError>>()?;
+        let basis = &*self.vk.basis;
+        let blinding_basis = self.vk.blinding_basis;
+        let opens = cfg_into_iter!(opens)
+            .map(|(commitment, a, b, point, eval, mut rng)| {
+                let public = Commitment(commitment.0);
+                let (opening, challenges, basis) = Self::open_hiding_prepared(
+                    basis,
+                    blinding_basis,
+                    commitment,
+                    &a,
+                    &b,
+                    point,
+                    eval,
+                    &mut rng,
+                    Some(vec![]),
+                )?;
+                Ok((public, opening, challenges, basis))
+            })
+            .collect::<Result<Vec<_>, IpaError>>()?;
+        self.amortize(opens)
+    }
+    ///opens the combination of the s-polynomials of the openings, which proves
+    ///all their final basis
+    #[allow(clippy::type_complexity)]
+    fn amortize<O: AmortizedVerifTrait<P, T>>(
+        &self,
+        opens: Vec<(O::Commit, O, Option<Vec<(Fr<P>, Fr<P>)>>, GroupAffine<P>)>,
+    ) -> Result<MultiOpening<P, O>, IpaError> {
+        let len = opens.len();
+        let mut openings = Vec::with_capacity(len);
+        let mut amortization = Vec::with_capacity(len);
+        let mut challenges = ChallengeGenerator::<P, T>::new_amortization();
+        for (commitment, open, s_challenges, basis) in opens {
+            open.digest_for_amortization(&commitment, &mut challenges);
+            challenges.digest_for_amortization(basis);
+            openings.push(open);
+            amortization.push((s_challenges.unwrap_or_default(), basis));
+        }
         let (point, combinations) = challenges.amortization_elements();
         let mut bs = vec![];
         let (_, s_poly, commitment) = amortization.into_iter().fold(
@@ -68,7 +159,7 @@ where
             |acc, val| {
                 let (combination, s_poly, commitment) = acc;
                 let (s_challenges, basis) = val;
-                let s_poly = Self::challenges_to_poly(s_challenges.unwrap(), combination) + s_poly;
+                let s_poly = Self::challenges_to_poly(s_challenges, combination) + s_poly;
 
                 let commitment = basis.mul(combination) + commitment;
                 bs.push(basis);
@@ -78,59 +169,167 @@ where
         );
         let eval = s_poly.evaluate(&point);
         let commitment = Commitment(commitment.into_affine());
-        let batch_opening = self.open(commitment, &*s_poly.coeffs, point, eval);
+        let batch_opening = self.open(commitment, &s_poly.coeffs, point, eval)?;
         debug_assert_eq!(openings.len(), bs.len());
-        let openings = openings.into_iter().zip(bs.into_iter()).collect();
-        MultiOpening {
+        let openings = openings.into_iter().zip(bs).collect();
+        Ok(MultiOpening {
             openings,
             batch_opening,
+        })
+    }
+    ///opens all the polynomials at the same point with one opening, the
+    ///proof doesn't grow with the number of polynomials apart from the evals
+    #[allow(clippy::type_complexity)]
+    pub fn batch_open_point(
+        &self,
+        opens: Vec<(Commitment<P, false>, &[Fr<P>], Fr<P>)>,
+        point: Fr<P>,
+    ) -> Result<PointOpening<P>, IpaError> {
+        if opens.is_empty() {
+            return Err(IpaError::EmptyBatch);
         }
+        let mut transcript = ChallengeGenerator::<P, T>::new_point_batch(&point);
+        for (commitment, _, eval) in opens.iter() {
+            transcript.digest_claim(commitment, eval);
+        }
+        let element = transcript.batch_element();
+        let combinations = successors(Some(Fr::<P>::one()), |e| Some(*e * element));
+
+        let len = opens.iter().map(|(_, coeffs, _)| coeffs.len()).max();
+        let mut coeffs = vec![Fr::<P>::zero(); len.unwrap_or_default()];
+        let mut evals = Vec::with_capacity(opens.len());
+        let mut combined = None;
+        for ((commitment, poly, eval), combination) in opens.into_iter().zip(combinations) {
+            for (coeff, e) in coeffs.iter_mut().zip(poly) {
+                *coeff += *e * combination;
+            }
+            let commitment = commitment * combination;
+            combined = Some(match combined {
+                Some(combined) => combined + commitment,
+                None => commitment,
+            });
+            evals.push(eval);
+        }
+        let eval = combine_evals::<P>(&evals, element);
+        let commitment = combined.ok_or(IpaError::EmptyBatch)?;
+        let opening = self.open(commitment, &coeffs, point, eval)?;
+        Ok(PointOpening { evals, opening })
     }
-    pub fn batch_verify<const HIDING: bool>(
+    ///same as verifying with the `VerifierKey` of the scheme
+    pub fn batch_verify_point(
         &self,
-        commitments: &[Commitment<P, HIDING>],
-        multi_open: MultiOpening<P>,
-    ) -> Option<Vec<Fr<P>>>
+        commitments: &[Commitment<P, false>],
+        point_opening: PointOpening<P>,
+    ) -> Result<Vec<Fr<P>>, IpaError>
     where
-        Commitment<P, HIDING>: Clone + Copy,
+        Commitment<P, false>: Copy,
+    {
+        self.vk.batch_verify_point(commitments, point_opening)
+    }
+    ///same as verifying with the `VerifierKey` of the scheme
+    pub fn batch_verify<O>(
+        &self,
+        commitments: &[O::Commit],
+        multi_open: MultiOpening<P, O>,
+    ) -> Result<Vec<Fr<P>>, IpaError>
+    where
+        O: AmortizedVerifTrait<P, T> + Send,
+        O::Commit: Sync,
+    {
+        self.vk.batch_verify(commitments, multi_open)
+    }
+    fn challenges_to_poly(
+        challenges: Vec<(Fr<P>, Fr<P>)>,
+        combination_element: Fr<P>,
+    ) -> DensePolynomial<Fr<P>> {
+        let coeffs = s_vector::<P>(&challenges)
+            .into_iter()
+            .map(|coeff| coeff * combination_element)
+            .collect();
+        DensePolynomial::from_coefficients_vec(coeffs)
+    }
+}
+
+impl<P, T> VerifierKey<P, T>
+where
+    P: ModelParameters + SWModelParameters,
+    Fr<P>: One,
+    T: Transcript<P>,
+{
+    ///combines the commitments with the homomorphism and checks the opening of
+    ///the combination, returning the evaluations
+    pub fn batch_verify_point(
+        &self,
+        commitments: &[Commitment<P, false>],
+        point_opening: PointOpening<P>,
+    ) -> Result<Vec<Fr<P>>, IpaError>
+    where
+        Commitment<P, false>: Copy,
+    {
+        let PointOpening { evals, opening } = point_opening;
+        if commitments.len() != evals.len() {
+            return Err(IpaError::LengthMismatch {
+                expected: commitments.len(),
+                found: evals.len(),
+            });
+        }
+        let mut transcript = ChallengeGenerator::<P, T>::new_point_batch(&opening.point);
+        for (commitment, eval) in commitments.iter().zip(evals.iter()) {
+            transcript.digest_claim(commitment, eval);
+        }
+        let element = transcript.batch_element();
+        let combinations = successors(Some(Fr::<P>::one()), |e| Some(*e * element));
+        let commitment = commitments
+            .iter()
+            .zip(combinations)
+            .map(|(commitment, combination)| *commitment * combination)
+            .reduce(|a, b| a + b)
+            .ok_or(IpaError::EmptyBatch)?;
+        if opening.eval != combine_evals::<P>(&evals, element) {
+            return Err(IpaError::BatchMismatch);
+        }
+        self.verify(commitment, opening)?;
+        Ok(evals)
+    }
+    pub fn batch_verify<O>(
+        &self,
+        commitments: &[O::Commit],
+        multi_open: MultiOpening<P, O>,
+    ) -> Result<Vec<Fr<P>>, IpaError>
+    where
+        O: AmortizedVerifTrait<P, T> + Send,
+        O::Commit: Sync,
     {
-        assert_eq!(commitments.len(), multi_open.openings.len());
         let MultiOpening {
             openings,
             batch_opening,
         } = multi_open;
-        let challenge_generator = openings.iter().fold(
-            ChallengeGenerator::new(),
-            |mut challenge_generator, elem| {
-                challenge_generator.digest_for_amortization(elem.1.clone());
-                challenge_generator
-            },
-        );
+        if commitments.len() != openings.len() {
+            return Err(IpaError::LengthMismatch {
+                expected: commitments.len(),
+                found: openings.len(),
+            });
+        }
+        if openings.is_empty() {
+            return Err(IpaError::EmptyBatch);
+        }
+        let mut challenge_generator = ChallengeGenerator::<P, T>::new_amortization();
+        for ((open, final_basis), commitment) in openings.iter().zip(commitments) {
+            open.digest_for_amortization(commitment, &mut challenge_generator);
+            challenge_generator.digest_for_amortization(*final_basis);
+        }
         let (combination_point, combination_element) = challenge_generator.amortization_elements();
 
-        let a: (Vec<_>, Vec<_>) = openings
-            .into_iter()
-            .zip(commitments.iter())
+        let (evals, amorti): (Vec<_>, Vec<_>) = cfg_into_iter!(openings)
+            .zip(commitments)
             .map(|((open, final_basis), commitment)| {
-                let Opening {
-                    point,
-                    eval,
-                    rounds,
-                    a,
-                } = open;
-                let u = ChallengeGenerator::inner_product_basis(&commitment, &point);
-                let (final_commit, b_poly) = Self::process_rounds(*commitment, point, eval, rounds);
-                let b = Self::eval_b_poly(&b_poly, point);
-                let amortization_eval = Self::eval_b_poly(&b_poly, combination_point);
-                let basis = final_basis;
-
-                (
-                    (final_commit, basis.mul(a) + u.mul(a * b), eval),
-                    (amortization_eval, final_basis),
-                )
+                let (eval, challenges) = open.verify_with_basis(self, commitment, final_basis)?;
+                let amortization_eval = eval_b_poly::<P>(&challenges, combination_point);
+                Ok((eval, (amortization_eval, final_basis)))
             })
+            .collect::<Result<Vec<_>, IpaError>>()?
+            .into_iter()
             .unzip();
-        let (opens, amorti) = a;
         let combinations = successors(Some(Fr::<P>::one()), |e| Some(*e * combination_element));
         let (amortization_eval, amortization_commitment) = amorti
             .into_iter()
@@ -139,58 +338,43 @@ where
                 let ((eval, commitment), combination) = elem;
                 (eval * combination, commitment.mul(combination))
             })
-            .reduce(|(a_e, a_c), (b_e, b_c)| (a_e + b_e, a_c + b_c))
-            .unwrap();
-        let commitment: Commitment<_, false> = Commitment(amortization_commitment.into_affine());
-        let eval = Self::verify(&self, commitment, batch_opening)?;
-        if amortization_eval != eval {
-            return None;
+            .fold(
+                (Fr::<P>::zero(), GroupProjective::zero()),
+                |(a_e, a_c), (b_e, b_c)| (a_e + b_e, a_c + b_c),
+            );
+        //the batch opening must be at the point the verifier derived, not one
+        //the prover picked
+        if batch_opening.point != combination_point {
+            return Err(IpaError::AmortizationMismatch);
         }
-        let mut all_valid = true;
-        let openings = opens
-            .into_iter()
-            .map(|(left, right, eval)| {
-                if left != right {
-                    all_valid = false;
-                }
-                eval
-            })
-            .collect();
-        if all_valid {
-            Some(openings)
-        } else {
-            None
+        let commitment: Commitment<_, false> = Commitment(amortization_commitment.into_affine());
+        let eval = Self::verify(self, commitment, batch_opening)?;
+        match amortization_eval == eval {
+            true => Ok(evals),
+            false => Err(IpaError::AmortizationMismatch),
         }
     }
-    fn challenges_to_poly(
-        challenges: Vec<(Fr<P>, Fr<P>)>,
-        combination_element: Fr<P>,
-    ) -> DensePolynomial<Fr<P>> {
-        let powers = std::iter::successors(Some(1_usize), |a| Some(a * 2));
-        let poly = challenges
-            .into_iter()
-            .rev()
-            .zip(powers)
-            .map(|((challenge, inverse), exp)| {
-                SparsePolynomial::from_coefficients_vec(vec![(0, inverse), (exp, challenge)])
-            })
-            .reduce(|a, b| a.mul(&b))
-            .unwrap();
-        let poly: DensePolynomial<_> = DenseOrSparsePolynomial::from(poly).into();
-        poly.mul(combination_element)
-    }
+}
+
+///the evaluations combined with the powers of the element
+fn combine_evals<P: SWModelParameters>(evals: &[Fr<P>], element: Fr<P>) -> Fr<P> {
+    evals
+        .iter()
+        .rev()
+        .fold(Fr::<P>::zero(), |acc, eval| acc * element + eval)
 }
 
 #[test]
 fn test_multi() {
-    use crate::{tests::commit_and_open, Init};
+    use crate::{tests::commit_and_open, transcript::Blake3Transcript, Init};
     use ark_pallas::PallasParameters;
-    use rand::thread_rng;
-    let scheme = IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, true, thread_rng());
+    use rand::{rngs::ThreadRng, thread_rng};
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, true, thread_rng()).unwrap();
     let (commitments, opens) = (0..4)
         .map(|_| {
             let a = commit_and_open(&scheme);
-            (a.0.clone(), a)
+            (a.0, a)
         })
         .unzip::<_, _, Vec<_>, Vec<_>>();
     let (polys, opens) = opens
@@ -209,7 +393,167 @@ fn test_multi() {
         })
         .collect::<Vec<_>>();
 
-    let multi_open = scheme.batch_open(opens);
-    let verif = scheme.batch_verify(&*commitments, multi_open);
-    assert!(verif.is_some())
+    let multi_open = scheme.batch_open(opens).unwrap();
+    let verif = scheme.batch_verify(&commitments, multi_open.clone());
+    assert!(verif.is_ok());
+
+    //a tampered eval changes the amortization challenges too
+    let mut tampered = multi_open.clone();
+    tampered.openings[1].0.eval += Fr::<PallasParameters>::one();
+    assert!(scheme.batch_verify(&commitments, tampered).is_err());
+
+    //the combined polynomial reopened at a point of the prover's choice
+    let mut generator =
+        ChallengeGenerator::<PallasParameters, Blake3Transcript>::new_amortization();
+    for ((open, basis), commitment) in multi_open.openings.iter().zip(&commitments) {
+        open.digest_for_amortization(commitment, &mut generator);
+        generator.digest_for_amortization(*basis);
+    }
+    let (point, combination) = generator.amortization_elements();
+    let combinations = successors(Some(Fr::<PallasParameters>::one()), |e| {
+        Some(*e * combination)
+    });
+    let (s_poly, commitment) = multi_open
+        .openings
+        .iter()
+        .zip(&commitments)
+        .zip(combinations)
+        .map(|(((open, basis), commitment), c)| {
+            let (_, challenges) = open
+                .clone()
+                .verify_with_basis(&scheme.vk, commitment, *basis)
+                .unwrap();
+            (
+                IpaScheme::<PallasParameters, ThreadRng>::challenges_to_poly(challenges, c),
+                basis.mul(c),
+            )
+        })
+        .fold(
+            (
+                DensePolynomial::from_coefficients_slice(&[]),
+                GroupProjective::zero(),
+            ),
+            |(a_p, a_c), (b_p, b_c)| (a_p + b_p, a_c + b_c),
+        );
+    let other_point = point + Fr::<PallasParameters>::one();
+    let eval = s_poly.evaluate(&other_point);
+    let reopened = scheme
+        .open(
+            Commitment(commitment.into_affine()),
+            &s_poly.coeffs,
+            other_point,
+            eval,
+        )
+        .unwrap();
+    let moved = MultiOpening {
+        openings: multi_open.openings,
+        batch_opening: reopened,
+    };
+    assert_eq!(
+        scheme.batch_verify(&commitments, moved).err(),
+        Some(IpaError::AmortizationMismatch)
+    );
+}
+
+#[test]
+fn test_point_batch() {
+    use crate::{tests::commit_and_open, Init};
+    use ark_pallas::PallasParameters;
+    use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+    use rand::thread_rng;
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, true, thread_rng()).unwrap();
+    let (commit, poly, point, eval) = commit_and_open(&scheme);
+    //polynomials of different sizes, all evaluated at the same point
+    let polys = [1, 16, 200]
+        .map(|len| poly[..len].to_vec())
+        .into_iter()
+        .chain([poly.clone()])
+        .collect::<Vec<_>>();
+    let claims = polys
+        .iter()
+        .map(|poly| {
+            let commit = scheme.commit(poly.clone()).unwrap();
+            let eval = DensePolynomial::from_coefficients_slice(poly).evaluate(&point);
+            (commit, &**poly, eval)
+        })
+        .collect::<Vec<_>>();
+    assert!(claims[3].0 == commit && claims[3].2 == eval);
+    let commitments = claims
+        .iter()
+        .map(|(commit, ..)| *commit)
+        .collect::<Vec<_>>();
+    let evals = claims.iter().map(|(.., eval)| *eval).collect::<Vec<_>>();
+
+    let opening = scheme.batch_open_point(claims.clone(), point).unwrap();
+    assert_eq!(opening.opening.rounds.len(), 8);
+    let verified = scheme.batch_verify_point(&commitments, opening.clone());
+    assert_eq!(verified.unwrap(), evals);
+
+    let mut wrong = opening.clone();
+    wrong.evals[1] += Fr::<PallasParameters>::one();
+    assert_eq!(
+        scheme.batch_verify_point(&commitments, wrong).err(),
+        Some(IpaError::BatchMismatch)
+    );
+    let mut swapped = commitments.clone();
+    swapped.swap(0, 1);
+    assert!(scheme
+        .batch_verify_point(&swapped, opening.clone())
+        .is_err());
+    assert!(scheme
+        .batch_verify_point(&commitments[1..], opening)
+        .is_err());
+
+    let mut claims = claims;
+    claims[2].2 += Fr::<PallasParameters>::one();
+    assert!(scheme.batch_open_point(claims, point).is_err());
+}
+
+#[test]
+fn test_multi_hiding() {
+    use crate::{tests::commit_and_open, Init};
+    use ark_pallas::PallasParameters;
+    use rand::thread_rng;
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, false, thread_rng()).unwrap();
+    let claims = (0..3)
+        .map(|_| {
+            let (_, poly, point, eval) = commit_and_open(&scheme);
+            let commit: UnsafeHidingCommitment<_> = scheme.commit(poly.clone()).unwrap();
+            (commit, poly, point, eval)
+        })
+        .collect::<Vec<_>>();
+    let opens = claims
+        .iter()
+        .map(|(commit, poly, point, eval)| (*commit, &**poly, *point, *eval))
+        .collect::<Vec<_>>();
+    let commitments = claims
+        .iter()
+        .map(|(commit, ..)| commit.clean())
+        .collect::<Vec<_>>();
+    let evals = claims.iter().map(|(.., eval)| *eval).collect::<Vec<_>>();
+
+    let multi_open = scheme.batch_open_hiding(opens).unwrap();
+    let verified = scheme.batch_verify(&commitments, multi_open.clone());
+    assert_eq!(verified.unwrap(), evals);
+
+    let mut wrong = multi_open.clone();
+    wrong.openings[0].1 = wrong.openings[1].1;
+    assert!(scheme.batch_verify(&commitments, wrong).is_err());
+    let mut wrong = multi_open.clone();
+    wrong.openings[2].0.z2 += Fr::<PallasParameters>::one();
+    assert!(scheme.batch_verify(&commitments, wrong).is_err());
+    let mut wrong = multi_open.clone();
+    wrong.openings[0].0.eval += Fr::<PallasParameters>::one();
+    assert!(scheme.batch_verify(&commitments, wrong).is_err());
+    let mut swapped = commitments;
+    swapped.swap(0, 1);
+    assert!(scheme.batch_verify(&swapped, multi_open).is_err());
+    assert!(matches!(
+        scheme.batch_open_hiding(vec![]),
+        Err(IpaError::EmptyBatch)
+    ));
 }
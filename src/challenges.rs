@@ -1,65 +1,193 @@
-use crate::{prove::Commitment, Fr};
+use crate::{
+    prove::Commitment,
+    transcript::{Blake3Transcript, Transcript},
+    Fr,
+};
 use ark_ec::{
     short_weierstrass_jacobian::GroupAffine, AffineCurve, ProjectiveCurve, SWModelParameters,
 };
-use ark_ff::UniformRand;
-use ark_serialize::CanonicalSerialize;
-use rand::{prelude::StdRng, SeedableRng};
 use std::marker::PhantomData;
 
+///domain separator for the transcript of a single opening
+const OPENING_DOMAIN: &[u8] = b"ipapc-opening-v1";
+///domain separator for the transcript of the amortization
+const AMORTIZATION_DOMAIN: &[u8] = b"ipapc-amortization-v1";
+///domain separator for the transcript of the decision of accumulators
+const DECISION_DOMAIN: &[u8] = b"ipapc-decision-v1";
+///domain separator for the transcript of the accumulation
+const ACCUMULATION_DOMAIN: &[u8] = b"ipapc-accumulation-v1";
+///domain separator for the transcript of openings at the same point
+const POINT_BATCH_DOMAIN: &[u8] = b"ipapc-point-batch-v1";
+///domain separator for the transcript of openings at sets of points
+const MULTI_POINT_DOMAIN: &[u8] = b"ipapc-multi-point-v1";
+
 #[derive(Clone)]
-pub struct ChallengeGenerator<P: SWModelParameters> {
-    data: Vec<u8>,
+pub struct ChallengeGenerator<P: SWModelParameters, T: Transcript<P> = Blake3Transcript> {
+    transcript: T,
     _model: PhantomData<P>,
 }
 
-impl<P: SWModelParameters> ChallengeGenerator<P> {
-    pub fn new() -> Self {
+impl<P: SWModelParameters, T: Transcript<P>> ChallengeGenerator<P, T> {
+    pub fn new_amortization() -> Self {
+        Self {
+            transcript: T::new(AMORTIZATION_DOMAIN),
+            _model: PhantomData,
+        }
+    }
+    pub fn new_decision() -> Self {
+        Self {
+            transcript: T::new(DECISION_DOMAIN),
+            _model: PhantomData,
+        }
+    }
+    pub fn new_accumulation() -> Self {
         Self {
-            data: vec![],
-            _model: PhantomData::default(),
+            transcript: T::new(ACCUMULATION_DOMAIN),
+            _model: PhantomData,
         }
     }
+    ///starts the transcript of a batch of openings at the point
+    pub fn new_point_batch(point: &Fr<P>) -> Self {
+        let mut generator = Self {
+            transcript: T::new(POINT_BATCH_DOMAIN),
+            _model: PhantomData,
+        };
+        generator.digest_scalar(point);
+        generator
+    }
+    pub fn new_multi_point() -> Self {
+        Self {
+            transcript: T::new(MULTI_POINT_DOMAIN),
+            _model: PhantomData,
+        }
+    }
+    ///starts the transcript of an opening, every challenge derived from it
+    ///depends on the commitment, the point, the claimed evaluation and all
+    ///the previous rounds
+    pub fn new_opening<const HIDING: bool>(
+        commitment: &Commitment<P, HIDING>,
+        point: &Fr<P>,
+        eval: &Fr<P>,
+    ) -> Self {
+        let mut generator = Self {
+            transcript: T::new(OPENING_DOMAIN),
+            _model: PhantomData,
+        };
+        generator.digest(&commitment.0);
+        generator.digest_scalar(point);
+        generator.digest_scalar(eval);
+        generator
+    }
     fn digest(&mut self, element: &GroupAffine<P>) {
-        element.serialize_unchecked(&mut self.data).unwrap()
+        self.transcript.absorb_point(element);
     }
     fn digest_scalar(&mut self, element: &Fr<P>) {
-        element.serialize_unchecked(&mut self.data).unwrap()
+        self.transcript.absorb_scalar(element);
+    }
+    ///digests an opening for the amortization: its commitment, point, claimed
+    ///evaluation, the messages of the rounds and the final ones
+    pub fn digest_opening<const HIDING: bool>(
+        &mut self,
+        commitment: &Commitment<P, HIDING>,
+        point: &Fr<P>,
+        eval: &Fr<P>,
+        rounds: &[(GroupAffine<P>, GroupAffine<P>)],
+        final_points: &[GroupAffine<P>],
+        final_scalars: &[Fr<P>],
+    ) {
+        self.digest(&commitment.0);
+        self.digest_scalar(point);
+        self.digest_scalar(eval);
+        for (lj, rj) in rounds {
+            self.digest(lj);
+            self.digest(rj);
+        }
+        final_points.iter().for_each(|point| self.digest(point));
+        final_scalars
+            .iter()
+            .for_each(|scalar| self.digest_scalar(scalar));
     }
-    ///digests commitments to generate the elements for amortization
-    pub fn digest_for_amortization(&mut self, commitment: GroupAffine<P>) {
-        commitment.serialize_unchecked(&mut self.data).unwrap()
+    ///digests the final basis of an opening, after the opening itself
+    pub fn digest_for_amortization(&mut self, final_basis: GroupAffine<P>) {
+        self.digest(&final_basis)
     }
     ///generates the element for the lineal combination and the evaluation point
-    pub fn amortization_elements(self) -> (Fr<P>, Fr<P>) {
-        let mut rng = self.generate_rng();
-        (<Fr<P>>::rand(&mut rng), <Fr<P>>::rand(&mut rng))
+    pub fn amortization_elements(mut self) -> (Fr<P>, Fr<P>) {
+        (self.squeeze(), self.squeeze())
+    }
+
+    ///digests an accumulator, so the combination depends on all of them
+    pub fn digest_accumulator(&mut self, challenges: &[Fr<P>], final_basis: &GroupAffine<P>) {
+        challenges
+            .iter()
+            .for_each(|challenge| self.digest_scalar(challenge));
+        self.digest(final_basis);
+    }
+    ///generates the element for the lineal combination of the accumulators
+    pub fn decision_element(mut self) -> Fr<P> {
+        self.squeeze()
+    }
+    ///generates the element for the lineal combination of the accumulators
+    ///and the point where the combination is opened
+    pub fn accumulation_elements(mut self) -> (Fr<P>, Fr<P>) {
+        (self.squeeze(), self.squeeze())
     }
 
-    pub fn inner_product_basis<const HIDING: bool>(
+    ///digests a commitment with its claimed evaluation
+    pub fn digest_claim<const HIDING: bool>(
+        &mut self,
         commitment: &Commitment<P, HIDING>,
-        point: &Fr<P>,
-    ) -> GroupAffine<P> {
-        let mut challenge_generator = Self::new();
-        challenge_generator.digest(&commitment.0);
-        challenge_generator.digest_scalar(point);
-        let mut rng = challenge_generator.generate_rng();
+        eval: &Fr<P>,
+    ) {
+        self.digest(&commitment.0);
+        self.digest_scalar(eval);
+    }
+    ///generates the element for the lineal combination of the claims
+    pub fn batch_element(mut self) -> Fr<P> {
+        self.squeeze()
+    }
+    ///digests a commitment with its points and claimed evaluations, the
+    ///number of points is digested first so the sets can't be regrouped
+    pub fn digest_point_set(
+        &mut self,
+        commitment: &Commitment<P, false>,
+        points: &[Fr<P>],
+        evals: &[Fr<P>],
+    ) {
+        self.digest(&commitment.0);
+        self.digest_scalar(&Fr::<P>::from(points.len() as u64));
+        for (point, eval) in points.iter().zip(evals) {
+            self.digest_scalar(point);
+            self.digest_scalar(eval);
+        }
+    }
+    ///generates the element for the lineal combination of the point sets
+    pub fn multi_point_element(&mut self) -> Fr<P> {
+        self.squeeze()
+    }
+    ///digests the commitment to the quotient and generates the opening point
+    pub fn quotient_challenge(&mut self, quotient: &GroupAffine<P>) -> Fr<P> {
+        self.digest(quotient);
+        self.squeeze()
+    }
 
+    pub fn inner_product_basis(&mut self) -> GroupAffine<P> {
         <GroupAffine<P>>::prime_subgroup_generator()
-            .mul(<Fr<P>>::rand(&mut rng))
+            .mul(self.squeeze())
             .into_affine()
     }
-    pub fn round_challenge(lj: &GroupAffine<P>, rj: &GroupAffine<P>) -> Fr<P> {
-        let mut challenge_generator = Self::new();
-        challenge_generator.digest(lj);
-        challenge_generator.digest(rj);
-        let mut rng = challenge_generator.generate_rng();
-
-        <Fr<P>>::rand(&mut rng)
+    pub fn round_challenge(&mut self, lj: &GroupAffine<P>, rj: &GroupAffine<P>) -> Fr<P> {
+        self.digest(lj);
+        self.digest(rj);
+        self.squeeze()
+    }
+    ///challenge for the final sigma protocol of zero-knowledge openings
+    pub fn sigma_challenge(&mut self, r: &GroupAffine<P>) -> Fr<P> {
+        self.digest(r);
+        self.squeeze()
     }
-    fn generate_rng(self) -> StdRng {
-        let Self { data, .. } = self;
-        let seed: [u8; 32] = blake3::hash(&*data).try_into().unwrap();
-        StdRng::from_seed(seed)
+    ///derives a challenge from everything absorbed so far
+    fn squeeze(&mut self) -> Fr<P> {
+        self.transcript.squeeze_challenge()
     }
 }
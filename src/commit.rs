@@ -1,42 +1,54 @@
-use crate::{CoeffsOrEvals, Commitment, Fr, IpaScheme, UnsafeHidingCommitment};
+use crate::{
+    transcript::{Blake3Transcript, Transcript},
+    CoeffsOrEvals, Commitment, Fr, IpaError, IpaScheme, UnsafeHidingCommitment,
+};
 use ark_ec::{AffineCurve, ProjectiveCurve, SWModelParameters};
 use ark_ff::UniformRand;
 use rand::Rng;
 use std::ops::{Add, Mul, Sub};
 
-pub trait CommitmentTrait<P, R>
+pub trait CommitmentTrait<P, R, T = Blake3Transcript>
 where
     P: SWModelParameters,
     Self: Add<Self> + Sub<Self> + Mul<Fr<P>> + Sized,
     R: Rng,
+    T: Transcript<P>,
 {
-    fn commit(scheme: &IpaScheme<P, R>, poly: impl Into<CoeffsOrEvals<P>>) -> Self;
+    fn commit(
+        scheme: &IpaScheme<P, R, T>,
+        poly: impl Into<CoeffsOrEvals<P>>,
+    ) -> Result<Self, IpaError>;
 }
 
-impl<P, R> CommitmentTrait<P, R> for Commitment<P, false>
+impl<P, R, T> CommitmentTrait<P, R, T> for Commitment<P, false>
 where
     P: SWModelParameters,
     R: Rng,
+    T: Transcript<P>,
 {
-    fn commit(scheme: &IpaScheme<P, R>, poly: impl Into<CoeffsOrEvals<P>>) -> Self {
-        let commitment = scheme.commit_simple(poly);
-        Self(commitment.into_affine())
+    fn commit(
+        scheme: &IpaScheme<P, R, T>,
+        poly: impl Into<CoeffsOrEvals<P>>,
+    ) -> Result<Self, IpaError> {
+        let commitment = scheme.commit_simple(poly)?;
+        Ok(Self(commitment.into_affine()))
     }
 }
 
-impl<P, R> CommitmentTrait<P, R> for UnsafeHidingCommitment<P>
+impl<P, R, T> CommitmentTrait<P, R, T> for UnsafeHidingCommitment<P>
 where
     P: SWModelParameters,
     R: Rng,
+    T: Transcript<P>,
 {
-    fn commit(scheme: &IpaScheme<P, R>, poly: impl Into<CoeffsOrEvals<P>>) -> Self {
-        let blinding_factor = {
-            let rng = &mut *(scheme.rng.borrow_mut());
-            Fr::<P>::rand(rng)
-        };
-        let commitment = scheme.commit_simple(poly);
-        let commitment = commitment + scheme.blinding_basis.mul(blinding_factor);
+    fn commit(
+        scheme: &IpaScheme<P, R, T>,
+        poly: impl Into<CoeffsOrEvals<P>>,
+    ) -> Result<Self, IpaError> {
+        let blinding_factor = Fr::<P>::rand(&mut *scheme.lock_rng());
+        let commitment = scheme.commit_simple(poly)?;
+        let commitment = commitment + scheme.vk.blinding_basis.mul(blinding_factor);
 
-        Self(commitment.into_affine(), blinding_factor)
+        Ok(Self(commitment.into_affine(), blinding_factor))
     }
 }
@@ -0,0 +1,65 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IpaError {
+    ///the polynomial has more coefficients or evaluations than the scheme supports
+    DegreeTooLarge { degree: usize, max_degree: usize },
+    ///two inputs that should have the same length don't
+    LengthMismatch { expected: usize, found: usize },
+    ///the proof doesn't have one round per halving of the basis
+    WrongRoundCount { expected: usize, found: usize },
+    ///the claimed evaluation is not the evaluation of the polynomial
+    EvaluationMismatch,
+    ///the last check of the opening failed, the proof is invalid
+    FinalCheckFailed,
+    ///the batched opening doesn't match the individual openings
+    AmortizationMismatch,
+    ///the opening of an accumulation doesn't match the accumulated claims
+    AccumulationMismatch,
+    ///the opening of a batch doesn't match the claims it combines
+    BatchMismatch,
+    ///the scheme can't be built with the requested size
+    InvalidSize(u8),
+    ///there is no hash to curve for this curve
+    UnsupportedCurve,
+    ///the domain tag of the hash to curve is too long
+    DomainTooLong,
+    ///a challenge was zero, so it can't be inverted
+    ZeroChallenge,
+    ///nothing to open or verify in a batch
+    EmptyBatch,
+    ///a point appears more than once in the same set
+    RepeatedPoint,
+}
+
+impl Display for IpaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpaError::DegreeTooLarge { degree, max_degree } => write!(
+                f,
+                "polynomial of size {degree} exceeds the maximum of {max_degree}"
+            ),
+            IpaError::LengthMismatch { expected, found } => {
+                write!(f, "expected length {expected}, found {found}")
+            }
+            IpaError::WrongRoundCount { expected, found } => {
+                write!(f, "expected {expected} rounds, found {found}")
+            }
+            IpaError::EvaluationMismatch => write!(f, "the evaluation doesn't match"),
+            IpaError::FinalCheckFailed => write!(f, "final check of the opening failed"),
+            IpaError::AmortizationMismatch => write!(f, "the amortized opening doesn't match"),
+            IpaError::AccumulationMismatch => {
+                write!(f, "the accumulation opening doesn't match")
+            }
+            IpaError::BatchMismatch => write!(f, "the batch opening doesn't match the claims"),
+            IpaError::InvalidSize(size) => write!(f, "unsupported size 2^{size}"),
+            IpaError::UnsupportedCurve => write!(f, "no hash to curve for this curve"),
+            IpaError::DomainTooLong => write!(f, "hash to curve domain too long"),
+            IpaError::ZeroChallenge => write!(f, "zero challenge"),
+            IpaError::EmptyBatch => write!(f, "empty batch"),
+            IpaError::RepeatedPoint => write!(f, "repeated point in a set"),
+        }
+    }
+}
+
+impl std::error::Error for IpaError {}
@@ -1,44 +1,51 @@
-use crate::{Commitment, Fr, IpaScheme};
+use crate::{
+    transcript::Transcript,
+    utils::{cfg_iter_mut, join},
+    Commitment, Fr, IpaError, IpaScheme,
+};
 use ark_ec::{
     short_weierstrass_jacobian::{GroupAffine, GroupProjective},
     AffineCurve, ModelParameters, ProjectiveCurve, SWModelParameters,
 };
 use ark_ff::{Field, One};
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
-use itertools::{izip, Itertools};
+use itertools::Itertools;
 use rand::Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::iter::successors;
 
-impl<P, R> IpaScheme<P, R>
+impl<P, R, T> IpaScheme<P, R, T>
 where
     P: ModelParameters + SWModelParameters,
     Fr<P>: One,
     R: Rng,
+    T: Transcript<P>,
 {
-    pub fn lagrange_commitments(&self) -> Vec<Commitment<P, false>> {
-        let basis = self.basis.iter().map(|e| e.into_projective()).collect_vec();
-        let commitments = self
-            .ifft(basis)
-            .into_iter()
-            .map(|e| Commitment(e))
+    pub fn lagrange_commitments(&self) -> Result<Vec<Commitment<P, false>>, IpaError> {
+        let basis = self
+            .vk
+            .basis
+            .iter()
+            .map(|e| e.into_projective())
             .collect_vec();
-        commitments
+        Ok(self.ifft(basis)?.into_iter().map(Commitment).collect_vec())
     }
 
-    fn ifft(&self, coeffs: Vec<GroupProjective<P>>) -> Vec<GroupAffine<P>> {
-        let inverted_size = Radix2EvaluationDomain::<Fr<P>>::new(coeffs.len())
-            .unwrap()
-            .size_as_field_element()
-            .inverse()
-            .unwrap();
-        let mut res = self.fft(coeffs).into_iter();
+    fn ifft(&self, coeffs: Vec<GroupProjective<P>>) -> Result<Vec<GroupAffine<P>>, IpaError> {
+        let domain = Radix2EvaluationDomain::<Fr<P>>::new(coeffs.len())
+            .ok_or(IpaError::InvalidSize(coeffs.len().trailing_zeros() as u8))?;
+        let inverted_size = domain.size_inv;
+        let mut res = Self::fft(coeffs, domain.group_gen).into_iter();
         let first = res.by_ref().next().unwrap();
-        [first]
+        Ok([first]
             .into_iter()
             .chain(res.rev())
             .map(|e| e.into_affine().mul(inverted_size).into_affine())
-            .collect_vec()
+            .collect_vec())
     }
-    fn fft(&self, coeffs: Vec<GroupProjective<P>>) -> Vec<GroupProjective<P>> {
+    ///`root` is a primitive root of unity of order `coeffs.len()`
+    fn fft(coeffs: Vec<GroupProjective<P>>, root: Fr<P>) -> Vec<GroupProjective<P>> {
         let len = coeffs.len();
         assert!(len.is_power_of_two());
         if coeffs.len().is_one() {
@@ -52,19 +59,23 @@ where
                 true => itertools::Either::Left(item),
                 false => itertools::Either::Right(item),
             });
-        let [even, odd] = [even, odd].map(|coeffs| self.fft(coeffs));
+        let square = root.square();
+        let (even, odd) = join(|| Self::fft(even, square), || Self::fft(odd, square));
         let mut result = coeffs;
         let (left, right) = result.split_at_mut(len / 2);
-        let domain = {
-            let domain = Radix2EvaluationDomain::<Fr<P>>::new(len).unwrap();
-            domain.elements()
-        };
-        //let domain = successors(Some(domain), |(a, b)| Some((*a * gen, fb * gen)));
-        for (even, odd, left, right, domain) in izip!(even, odd, left, right, domain) {
-            let rhs = odd.into_affine().mul(domain);
-            *left = even + rhs;
-            *right = even - rhs;
-        }
+        let domain = successors(Some(Fr::<P>::one()), |elem| Some(*elem * root))
+            .take(len / 2)
+            .collect_vec();
+        cfg_iter_mut!(left)
+            .zip(right)
+            .zip(even)
+            .zip(odd)
+            .zip(domain)
+            .for_each(|((((left, right), even), odd), domain)| {
+                let rhs = odd.into_affine().mul(domain);
+                *left = even + rhs;
+                *right = even - rhs;
+            });
         result
     }
 }
@@ -75,7 +86,8 @@ fn lagrange_commitment() {
     use ark_poly::{EvaluationDomain, Evaluations, GeneralEvaluationDomain};
     use rand::thread_rng;
     let scheme =
-        IpaScheme::<PallasParameters, _>::init(crate::Init::Seed(1), 3, true, thread_rng());
+        IpaScheme::<PallasParameters, _>::init(crate::Init::Seed(1), 3, true, thread_rng())
+            .unwrap();
     let domain = GeneralEvaluationDomain::new(8).unwrap();
     let evals = [1_i32, 0, 0, 0, 0, 0, 0, 0];
     let lcommit = |i| {
@@ -88,11 +100,11 @@ fn lagrange_commitment() {
             .take(evals.len())
             .collect_vec();
         let poly = Evaluations::from_vec_and_domain(evals, domain).interpolate();
-        let good_commit: Commitment<_, false> = scheme.commit(poly.coeffs);
+        let good_commit: Commitment<_, false> = scheme.commit(poly.coeffs).unwrap();
         good_commit
     };
     let good_commitments = (0..8).map(lcommit).collect_vec();
-    let commitments = scheme.lagrange_commitments();
+    let commitments = scheme.lagrange_commitments().unwrap();
     for (a, b) in good_commitments.iter().zip(commitments.iter()) {
         assert_eq!(a, b);
     }
@@ -0,0 +1,337 @@
+use crate::IpaError;
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, SWModelParameters};
+use ark_ff::{field_new, BigInteger, Field, One, PrimeField, SquareRootField, Zero};
+use ark_pallas::{Fq, Fr, PallasParameters};
+use ark_vesta::VestaParameters;
+use std::any::{Any, TypeId};
+
+///parameters of the simplified SWU map to a curve isogenous to `Self`, needed
+///because the Pasta curves have a = 0
+trait SwuParameters: SWModelParameters {
+    const CURVE_ID: &'static str;
+    ///coefficients of the isogenous curve
+    const ISO_A: Self::BaseField;
+    const ISO_B: Self::BaseField;
+    ///non square used by the map
+    const Z: Self::BaseField;
+    ///coefficients of the rational maps of the 3-isogeny
+    const ISOGENY: [Self::BaseField; 13];
+}
+
+impl SwuParameters for PallasParameters {
+    const CURVE_ID: &'static str = "pallas";
+    const ISO_A: Fq = field_new!(
+        Fq,
+        "10949663248450308183708987909873589833737836120165333298109615750520499732811"
+    );
+    const ISO_B: Fq = field_new!(Fq, "1265");
+    const Z: Fq = field_new!(Fq, "-13");
+    const ISOGENY: [Fq; 13] = [
+        field_new!(
+            Fq,
+            "6432893846517566412420610278260439325191790329320346825767705947633326140075"
+        ),
+        field_new!(
+            Fq,
+            "23989696149150192365340222745168215001509815558210986772351135915822265203574"
+        ),
+        field_new!(
+            Fq,
+            "10492611921771203378452795982353351666191589197598957448093274638589204800759"
+        ),
+        field_new!(
+            Fq,
+            "12865787693035132824841220556520878650383580658640693651535411895266652280192"
+        ),
+        field_new!(
+            Fq,
+            "13271109177048389296812780941310096270046944650307955939477485891950613419807"
+        ),
+        field_new!(
+            Fq,
+            "22768321103861051515190775253992702316905399997697804654926324362758820947460"
+        ),
+        field_new!(
+            Fq,
+            "11793638718615538422771118843477472096184948937087302513907460903994431256804"
+        ),
+        field_new!(
+            Fq,
+            "11994848074575096182670111372584107500754907779105493386175567957911132601787"
+        ),
+        field_new!(
+            Fq,
+            "28823569610051396102362669851238297121581474897215657071023781420043761726004"
+        ),
+        field_new!(
+            Fq,
+            "1072148974419594402070101713043406554198631721553391137627950991272221023311"
+        ),
+        field_new!(
+            Fq,
+            "5432652610908059517272798285879155923388888734491153551238890455750936314542"
+        ),
+        field_new!(
+            Fq,
+            "10408918692925056833786833257634153023990087029210292532869619559576527581706"
+        ),
+        field_new!(
+            Fq,
+            "28948022309329048855892746252171976963363056481941560715954676764349967629797"
+        ),
+    ];
+}
+
+impl SwuParameters for VestaParameters {
+    const CURVE_ID: &'static str = "vesta";
+    const ISO_A: Fr = field_new!(
+        Fr,
+        "17413348858408915339762682399132325137863850198379221683097628341577494210225"
+    );
+    const ISO_B: Fr = field_new!(Fr, "1265");
+    const Z: Fr = field_new!(Fr, "-13");
+    const ISOGENY: [Fr; 13] = [
+        field_new!(
+            Fr,
+            "25731575386070265649682441113041757300767161317281464337493104665238544842753"
+        ),
+        field_new!(
+            Fr,
+            "13377367003779316331268047403600734872799183885837485433911493934102207511749"
+        ),
+        field_new!(
+            Fr,
+            "11064082577423419940183149293632076317553812518550871517841037420579891210813"
+        ),
+        field_new!(
+            Fr,
+            "22515128462811482443472135973911537638171266152621281295306466582083726737451"
+        ),
+        field_new!(
+            Fr,
+            "4604213796697651557841441623718706001740429044770779386484474413346415813353"
+        ),
+        field_new!(
+            Fr,
+            "9250006497141849826017568406346290940322373181457057184910582871723433210981"
+        ),
+        field_new!(
+            Fr,
+            "8577191795356755216560813704347252433589053772427154779164368221746181614251"
+        ),
+        field_new!(
+            Fr,
+            "21162694656554182593580396827886355918081120183889566406795618341247785229923"
+        ),
+        field_new!(
+            Fr,
+            "11620280474556824258112134491145636201000922752744881519070727793732904824884"
+        ),
+        field_new!(
+            Fr,
+            "13937936667454727226911322269564285204582212380194126516142098360337545123123"
+        ),
+        field_new!(
+            Fr,
+            "21380331849711001764708535561664047484292171808126992769566582994216305194078"
+        ),
+        field_new!(
+            Fr,
+            "27750019491425549478052705219038872820967119544371171554731748615170299632943"
+        ),
+        field_new!(
+            Fr,
+            "28948022309329048855892746252171976963363056481941647379679742748393362947557"
+        ),
+    ];
+}
+
+///hashes `message` to the curve `P`, fails for curves without a known hash to
+///curve, only Pallas and Vesta for now.
+///Compatible with the `hash_to_curve` of the `pasta_curves` crate.
+pub(crate) fn hash_to_curve<P: SWModelParameters>(
+    domain: &str,
+    message: &[u8],
+) -> Result<GroupAffine<P>, IpaError> {
+    fn cast<P: SWModelParameters, Q: SWModelParameters>(
+        point: Result<GroupAffine<Q>, IpaError>,
+    ) -> Result<GroupAffine<P>, IpaError> {
+        let point: Box<dyn Any> = Box::new(point?);
+        point
+            .downcast()
+            .map(|point| *point)
+            .map_err(|_| IpaError::UnsupportedCurve)
+    }
+    let id = TypeId::of::<P>();
+    if id == TypeId::of::<PallasParameters>() {
+        cast(hash::<PallasParameters>(domain, message))
+    } else if id == TypeId::of::<VestaParameters>() {
+        cast(hash::<VestaParameters>(domain, message))
+    } else {
+        Err(IpaError::UnsupportedCurve)
+    }
+}
+
+fn hash<P>(domain: &str, message: &[u8]) -> Result<GroupAffine<P>, IpaError>
+where
+    P: SwuParameters,
+    P::BaseField: PrimeField,
+{
+    let [u0, u1] = hash_to_field::<P::BaseField>(P::CURVE_ID, domain, message)?;
+    //the isogeny is a group homomorphism, so the points can be added after it
+    let [q0, q1] = [u0, u1].map(|u| {
+        let (x, y) = map_to_curve::<P>(u);
+        iso_map::<P>(x, y)
+    });
+    Ok(q0 + q1)
+}
+
+///expand_message_xmd with BLAKE2b, producing two field elements from 64 bytes each
+fn hash_to_field<F: PrimeField>(
+    curve_id: &str,
+    domain: &str,
+    message: &[u8],
+) -> Result<[F; 2], IpaError> {
+    const CHUNKLEN: usize = 64;
+    const R_IN_BYTES: usize = 128;
+    if 22 + curve_id.len() + domain.len() >= 256 {
+        return Err(IpaError::DomainTooLong);
+    }
+    let dst = [
+        domain.as_bytes(),
+        b"-",
+        curve_id.as_bytes(),
+        b"_XMD:BLAKE2b_SSWU_RO_",
+        &[(22 + curve_id.len() + domain.len()) as u8],
+    ]
+    .concat();
+    let hasher = blake2b_simd::Params::new()
+        .hash_length(CHUNKLEN)
+        .personal(&[0; 16])
+        .to_state();
+
+    let b_0 = hasher
+        .clone()
+        .update(&[0; R_IN_BYTES])
+        .update(message)
+        .update(&[0, (CHUNKLEN * 2) as u8, 0])
+        .update(&dst)
+        .finalize();
+    let b_1 = hasher
+        .clone()
+        .update(b_0.as_bytes())
+        .update(&[1])
+        .update(&dst)
+        .finalize();
+    let xored = b_0
+        .as_bytes()
+        .iter()
+        .zip(b_1.as_bytes())
+        .map(|(a, b)| a ^ b)
+        .collect::<Vec<_>>();
+    let b_2 = hasher
+        .clone()
+        .update(&xored)
+        .update(&[2])
+        .update(&dst)
+        .finalize();
+    Ok([b_1, b_2].map(|bytes| F::from_be_bytes_mod_order(bytes.as_bytes())))
+}
+
+///simplified SWU map to the isogenous curve
+fn map_to_curve<P>(u: P::BaseField) -> (P::BaseField, P::BaseField)
+where
+    P: SwuParameters,
+    P::BaseField: PrimeField,
+{
+    let (a, b, z) = (P::ISO_A, P::ISO_B, P::Z);
+    let g = |x: P::BaseField| (x.square() + a) * x + b;
+    let sgn0 = |x: P::BaseField| x.into_repr().is_odd();
+
+    let z_u2 = z * u.square();
+    let ta = z_u2.square() + z_u2;
+    let x1 = match ta.inverse() {
+        Some(tv1) => -b / a * (tv1 + P::BaseField::one()),
+        None => b / (z * a),
+    };
+    let (x, y) = match g(x1).sqrt() {
+        Some(y) => (x1, y),
+        None => {
+            //g(x2) is a square whenever g(x1) is not
+            let x2 = z_u2 * x1;
+            (x2, g(x2).sqrt().unwrap())
+        }
+    };
+    match sgn0(u) == sgn0(y) {
+        true => (x, y),
+        false => (x, -y),
+    }
+}
+
+///maps a point of the isogenous curve to `P`, the kernel goes to the identity
+fn iso_map<P>(x: P::BaseField, y: P::BaseField) -> GroupAffine<P>
+where
+    P: SwuParameters,
+    P::BaseField: PrimeField,
+{
+    let k = P::ISOGENY;
+    let num_x = ((k[0] * x + k[1]) * x + k[2]) * x + k[3];
+    let div_x = (x + k[4]) * x + k[5];
+    let num_y = (((k[6] * x + k[7]) * x + k[8]) * x + k[9]) * y;
+    let div_y = ((x + k[10]) * x + k[11]) * x + k[12];
+    match (div_x.inverse(), div_y.inverse()) {
+        (Some(div_x), Some(div_y)) => GroupAffine::new(num_x * div_x, num_y * div_y, false),
+        _ => GroupAffine::zero(),
+    }
+}
+
+#[test]
+fn test_vectors() {
+    fn to_hex<F: PrimeField>(elem: F) -> String {
+        elem.into_repr()
+            .to_bytes_be()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+    fn check<P: SWModelParameters>(message: &[u8], x: &str, y: &str)
+    where
+        P::BaseField: PrimeField,
+    {
+        let point = hash_to_curve::<P>("ipapc-test", message).unwrap();
+        assert!(point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve());
+        assert_eq!(to_hex(point.x), x);
+        assert_eq!(to_hex(point.y), y);
+    }
+    //generated with the pasta_curves crate
+    check::<PallasParameters>(
+        b"",
+        "21405e426d0e3ff193b010c77dfe9a72fbd8485c2c63923a37f68ca7df8034ed",
+        "255be26fc06c8eb3b72a2ec9141ae1d324470ba7123a6a5e431de517fd39ddba",
+    );
+    check::<VestaParameters>(
+        b"",
+        "2fee57b1aa3d1ee2556108d2aa8e23b963dcd5d5e06db89add8ad9e899581b39",
+        "1e006451cd8ac79eb03bda5ebf060e1f30cf11831c4fb90aa49e1ca5baf4ca6e",
+    );
+    check::<PallasParameters>(
+        &0_u64.to_le_bytes(),
+        "37d5748ed44876420b8ff07e6f7ee1ca4784299d1ad28b731bbfba0955318922",
+        "12b15afd35d1835cf65181f9a3242fb05df9f9694aa09ede8fae01cfa976dadf",
+    );
+    check::<VestaParameters>(
+        &0_u64.to_le_bytes(),
+        "25e36dbdcb810558db98ab9009f7995d9ebf05d2001b9d0452c5cb51648509c8",
+        "326286304b9c843ee513b76f47c683d210d55e5cdbcb9f4243625acf7228f4f4",
+    );
+    check::<PallasParameters>(
+        &1_u64.to_le_bytes(),
+        "3730c9a2893a011274870acbf2c10cd2cd81fdb92b5051c1e2224833d8fa8402",
+        "38178711de04f553ecd2b8ec80e5826f4025ff28042af2b37f71101398373c26",
+    );
+    check::<VestaParameters>(
+        &1_u64.to_le_bytes(),
+        "0d9d672f1938df89ce1aa0228e9c473c0fbac862ac4a08f35c16e24e9de2ad9e",
+        "1e6d13d9e29126011cce78dd509ec197504758e9a2fbba6cb3a3716a87441125",
+    );
+}
@@ -78,7 +78,7 @@ fn homomorphisms() {
     let make_scheme = || {
         let rng = StdRng::seed_from_u64(1);
         (
-            IpaScheme::init(Init::<P>::Seed(1), 2, true, thread_rng()),
+            IpaScheme::<P, _>::init(Init::Seed(1), 2, true, thread_rng()).unwrap(),
             rng,
         )
     };
@@ -92,13 +92,13 @@ fn homomorphisms() {
     let check = |poly: Vec<_>| {
         let (scheme, _rng) = make_scheme();
         //let commit = scheme.commit_hiding(poly.clone(), &mut rng);
-        let commit = scheme.commit(poly.clone());
+        let commit = scheme.commit(poly.clone()).unwrap();
         let point = Fr::<P>::from(43);
         let eval = {
-            let poly = DensePolynomial::<Fr<P>>::from_coefficients_slice(&*poly);
+            let poly = DensePolynomial::<Fr<P>>::from_coefficients_slice(&poly);
             poly.evaluate(&point)
         };
-        let open: HidingOpening<_> = scheme.open(commit, &*poly, point, eval);
+        let open: HidingOpening<_> = scheme.open(commit, &poly, point, eval).unwrap();
         scheme.verify(commit.into(), open).unwrap()
     };
     let c1 = check(p1);
@@ -2,46 +2,75 @@ use ark_ec::{
     short_weierstrass_jacobian::{GroupAffine, GroupProjective},
     AffineCurve, ModelParameters, SWModelParameters,
 };
-use ark_ff::{Field, One, PrimeField};
+use ark_ff::{Field, One, PrimeField, Zero};
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, Evaluations, Radix2EvaluationDomain,
 };
 use commit::CommitmentTrait;
+pub use error::IpaError;
+use hash_to_curve::hash_to_curve;
 use itertools::Itertools;
 pub use prove::{Commitment, HidingOpening, Opening, UnsafeHidingCommitment};
 use rand::{prelude::StdRng, Rng, SeedableRng};
 use std::{
-    cell::RefCell,
-    convert::identity,
     fmt::Debug,
     iter::{repeat, successors},
+    marker::PhantomData,
+    sync::{Mutex, MutexGuard, PoisonError},
 };
+use transcript::{Blake3Transcript, Transcript};
 
+pub mod accumulation;
 pub mod amortization;
 mod challenges;
 mod commit;
+mod error;
 mod fft;
+mod hash_to_curve;
 mod homomorphism;
+pub mod multiopen;
 mod open;
+#[cfg(feature = "poly-commit")]
+pub mod poly_commit;
+pub mod poseidon;
 pub mod prove;
+mod serialization;
+mod srs;
 #[cfg(test)]
 mod tests;
+pub mod transcript;
 mod utils;
 mod verify;
 
 //type Poly<Fr> = DensePolynomial<Fr>;
 type Fr<P> = <GroupAffine<P> as AffineCurve>::ScalarField;
-pub struct IpaScheme<P, R>
+///the committer key, it holds everything the prover needs while verification
+///only needs the `VerifierKey`
+pub struct IpaScheme<P, R, T = Blake3Transcript>
 where
     P: ModelParameters + SWModelParameters,
     R: Rng,
+    T: Transcript<P>,
 {
-    basis: Vec<GroupAffine<P>>,
+    vk: VerifierKey<P, T>,
     ///second basis to commit to evals linearly
     evaluation_basis: Option<Vec<GroupAffine<P>>>,
+    ///behind a lock so the scheme is `Sync` when `R` is `Send`, like `StdRng`
+    rng: Mutex<R>,
+}
+
+///the part of the scheme needed to verify, without the rng and the evaluation
+///basis
+pub struct VerifierKey<P, T = Blake3Transcript>
+where
+    P: ModelParameters + SWModelParameters,
+    T: Transcript<P>,
+{
+    basis: Vec<GroupAffine<P>>,
     blinding_basis: GroupAffine<P>,
     max_degree: usize,
-    rng: RefCell<R>,
+    ///only a type, so the key is `Send + Sync` whatever the transcript
+    _transcript: PhantomData<fn() -> T>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -51,73 +80,149 @@ pub struct Blinding<P: SWModelParameters>(Fr<P>);
 pub enum Init<T: SWModelParameters> {
     Seed(u64),
     Elements(Vec<GroupAffine<T>>, GroupAffine<T>),
+    ///derives the basis with hash to curve under the given domain tag, the i-th
+    ///point hashes `i` as 8 bytes little endian and the blinding basis hashes
+    ///the empty message. Only available for Pallas and Vesta
+    HashToCurve(String),
 }
 
-impl<P, R> IpaScheme<P, R>
+impl<P, R, T> IpaScheme<P, R, T>
 where
     P: ModelParameters + SWModelParameters,
     Fr<P>: One,
     R: Rng,
+    T: Transcript<P>,
 {
-    pub fn init(init: Init<P>, max_size: u8, commit_to_evals: bool, rng: R) -> Self {
-        let max_degree = 2_usize.pow(max_size as u32);
-        let (basis, blinding_basis) = init.to_elements(max_degree);
+    ///`max_size` is the log2 of the maximum number of coefficients, it must be at
+    ///least 1 and small enough for the FFT domain of the scalar field
+    pub fn init(
+        init: Init<P>,
+        max_size: u8,
+        commit_to_evals: bool,
+        rng: R,
+    ) -> Result<Self, IpaError> {
+        Self::from_verifier_key(VerifierKey::init(init, max_size)?, commit_to_evals, rng)
+    }
+    pub(crate) fn from_verifier_key(
+        vk: VerifierKey<P, T>,
+        commit_to_evals: bool,
+        rng: R,
+    ) -> Result<Self, IpaError> {
         let scheme = Self {
-            basis,
+            vk,
             evaluation_basis: None,
-            max_degree,
-            blinding_basis,
-            rng: RefCell::new(rng),
+            rng: Mutex::new(rng),
         };
         match commit_to_evals {
             true => {
                 let eval_basis = scheme
-                    .lagrange_commitments()
+                    .lagrange_commitments()?
                     .into_iter()
                     .map(|point| point.0)
                     .collect_vec();
-                Self {
+                Ok(Self {
                     evaluation_basis: Some(eval_basis),
                     ..scheme
-                }
+                })
             }
-            false => scheme,
+            false => Ok(scheme),
         }
     }
-    fn commit_simple(&self, poly: impl Into<CoeffsOrEvals<P>>) -> GroupProjective<P> {
+    ///a panic while holding the lock can't leave the rng in an unusable state,
+    ///so poisoning is ignored
+    fn lock_rng(&self) -> MutexGuard<'_, R> {
+        self.rng.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+    pub fn verifier_key(&self) -> &VerifierKey<P, T> {
+        &self.vk
+    }
+    ///a scheme over the first 2^log_size elements of the basis, with log_size
+    ///rounds per proof. Commitments to polynomials that fit in it are the same
+    ///as with the full scheme, so they can be opened with either
+    pub fn trim<R2: Rng>(&self, log_size: u8, rng: R2) -> Result<IpaScheme<P, R2, T>, IpaError> {
+        let vk = self.vk.trim(log_size)?;
+        IpaScheme::from_verifier_key(vk, self.evaluation_basis.is_some(), rng)
+    }
+    ///polynomials shorter than `max_degree` are committed with a prefix of the
+    ///basis, as if they were padded with zeros
+    fn commit_simple(
+        &self,
+        poly: impl Into<CoeffsOrEvals<P>>,
+    ) -> Result<GroupProjective<P>, IpaError> {
         let poly: CoeffsOrEvals<P> = poly.into();
-        let (poly, basis) = self.poly_to_msm_vecs(poly);
-        debug_assert_eq!(poly.len(), self.max_degree);
-        let bases = &*basis;
+        let (poly, basis) = self.poly_to_msm_vecs(poly)?;
         let coeffs = poly.into_iter().map(|e| e.into_repr()).collect::<Vec<_>>();
-        let scalars = &*coeffs;
-        let result = ark_ec::msm::VariableBaseMSM::multi_scalar_mul(bases, scalars);
-        result
+        Ok(ark_ec::msm::VariableBaseMSM::multi_scalar_mul(
+            &basis[..coeffs.len()],
+            &coeffs,
+        ))
     }
-    fn poly_to_msm_vecs(&self, poly: CoeffsOrEvals<P>) -> (Vec<Fr<P>>, &Vec<GroupAffine<P>>) {
-        //let basis = &self.basis;
+    #[allow(clippy::type_complexity)]
+    fn poly_to_msm_vecs(
+        &self,
+        poly: CoeffsOrEvals<P>,
+    ) -> Result<(Vec<Fr<P>>, &[GroupAffine<P>]), IpaError> {
         match (poly, &self.evaluation_basis) {
-            (CoeffsOrEvals::Coeffs(coeffs), _) => (coeffs, &self.basis),
-            (CoeffsOrEvals::Evals(evals), None) => {
-                //interpolate
-                let domain = Radix2EvaluationDomain::<Fr<P>>::new(evals.len()).unwrap();
+            (CoeffsOrEvals::Coeffs(coeffs), _) => {
+                self.check_degree(coeffs.len())?;
+                Ok((coeffs, &self.vk.basis))
+            }
+            (CoeffsOrEvals::Evals(evals), Some(basis)) if evals.len() == self.vk.max_degree => {
+                Ok((evals, basis))
+            }
+            //the evaluation basis is only for the full domain, smaller domains
+            //are interpolated
+            (CoeffsOrEvals::Evals(evals), _) => {
+                self.check_evals(evals.len())?;
+                let domain = Radix2EvaluationDomain::<Fr<P>>::new(evals.len())
+                    .ok_or(IpaError::InvalidSize(evals.len().trailing_zeros() as u8))?;
                 let evals = Evaluations::from_vec_and_domain(evals, domain);
-                (evals.interpolate().coeffs, &self.basis)
+                Ok((evals.interpolate().coeffs, &self.vk.basis))
             }
-            (CoeffsOrEvals::Evals(evals), Some(basis)) => (evals, basis),
         }
     }
-    pub fn commit<C: CommitmentTrait<P, R>>(&self, poly: impl Into<CoeffsOrEvals<P>>) -> C {
+    fn check_degree(&self, degree: usize) -> Result<(), IpaError> {
+        match degree <= self.vk.max_degree {
+            true => Ok(()),
+            false => Err(IpaError::DegreeTooLarge {
+                degree,
+                max_degree: self.vk.max_degree,
+            }),
+        }
+    }
+    ///evaluations must be over a domain of power of two size
+    fn check_evals(&self, len: usize) -> Result<(), IpaError> {
+        self.check_degree(len)?;
+        match len.is_power_of_two() {
+            true => Ok(()),
+            false => Err(IpaError::LengthMismatch {
+                expected: len.next_power_of_two(),
+                found: len,
+            }),
+        }
+    }
+    ///pads the coefficients with zeros up to `max_degree`
+    fn pad(&self, coeffs: &[Fr<P>]) -> Result<Vec<Fr<P>>, IpaError> {
+        self.check_degree(coeffs.len())?;
+        let mut coeffs = coeffs.to_vec();
+        coeffs.resize(self.vk.max_degree, Fr::<P>::zero());
+        Ok(coeffs)
+    }
+    pub fn commit<C: CommitmentTrait<P, R, T>>(
+        &self,
+        poly: impl Into<CoeffsOrEvals<P>>,
+    ) -> Result<C, IpaError> {
         C::commit(self, poly)
     }
     fn b(&self, z: Fr<P>) -> Vec<Fr<P>> {
         successors(Some(<Fr<P>>::one()), |previous| Some(*previous * z))
-            .take(self.basis.len())
+            .take(self.vk.basis.len())
             .collect()
     }
     ///returns the reference string as commitments
     pub fn string(&self) -> Vec<Commitment<P, false>> {
-        self.basis
+        self.vk
+            .basis
             .iter()
             .cloned()
             .map(|elem| Commitment(elem))
@@ -125,26 +230,83 @@ where
     }
 }
 
+impl<P, T> VerifierKey<P, T>
+where
+    P: ModelParameters + SWModelParameters,
+    T: Transcript<P>,
+{
+    ///same as `IpaScheme::init`, for verifiers that don't need to commit
+    pub fn init(init: Init<P>, max_size: u8) -> Result<Self, IpaError> {
+        let max_degree = 2_usize
+            .checked_pow(max_size as u32)
+            .filter(|degree| *degree > 1)
+            .filter(|degree| Radix2EvaluationDomain::<Fr<P>>::new(*degree).is_some())
+            .ok_or(IpaError::InvalidSize(max_size))?;
+        let (basis, blinding_basis) = init.to_elements(max_degree)?;
+        Ok(Self {
+            basis,
+            blinding_basis,
+            max_degree,
+            _transcript: PhantomData,
+        })
+    }
+    ///the key over the first 2^log_size elements of the basis, it verifies the
+    ///openings of `IpaScheme::trim` with the same `log_size`
+    pub fn trim(&self, log_size: u8) -> Result<Self, IpaError> {
+        if log_size as u32 > self.max_degree.trailing_zeros() {
+            return Err(IpaError::InvalidSize(log_size));
+        }
+        let size = 2_usize.pow(log_size as u32);
+        let elements = Init::Elements(self.basis[..size].to_vec(), self.blinding_basis);
+        Self::init(elements, log_size)
+    }
+}
+
+impl<P, T> Clone for VerifierKey<P, T>
+where
+    P: ModelParameters + SWModelParameters,
+    T: Transcript<P>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            basis: self.basis.clone(),
+            blinding_basis: self.blinding_basis,
+            max_degree: self.max_degree,
+            _transcript: PhantomData,
+        }
+    }
+}
+
 impl<T: SWModelParameters> Init<T> {
-    fn to_elements(self, size: usize) -> (Vec<GroupAffine<T>>, GroupAffine<T>) {
+    #[allow(clippy::wrong_self_convention)]
+    fn to_elements(self, size: usize) -> Result<(Vec<GroupAffine<T>>, GroupAffine<T>), IpaError> {
         match self {
             Init::Seed(seed) => {
                 let mut rng = StdRng::seed_from_u64(seed);
-                let mut elems = repeat(())
-                    .map(|_| {
-                        let bytes: [u8; 32] = rng.gen();
-                        let x = <T::BaseField as Field>::from_random_bytes(&bytes)?;
-                        GroupAffine::<T>::get_point_from_x(x, false)
-                    })
-                    .filter_map(identity);
+                let mut elems = repeat(()).filter_map(|_| {
+                    let bytes: [u8; 32] = rng.gen();
+                    let x = <T::BaseField as Field>::from_random_bytes(&bytes)?;
+                    GroupAffine::<T>::get_point_from_x(x, false)
+                });
                 let blind = elems.next().unwrap();
-                (elems.take(size).collect(), blind)
+                Ok((elems.take(size).collect(), blind))
+            }
+            Init::HashToCurve(domain) => {
+                let hash = |message: &[u8]| hash_to_curve::<T>(&domain, message);
+                let elems = (0..size as u64)
+                    .map(|i| hash(&i.to_le_bytes()))
+                    .collect::<Result<_, _>>()?;
+                Ok((elems, hash(&[])?))
             }
             Init::Elements(mut elems, blinding) => {
-                assert!(elems.len() >= size);
+                if elems.len() < size {
+                    return Err(IpaError::LengthMismatch {
+                        expected: size,
+                        found: elems.len(),
+                    });
+                }
                 elems.truncate(size);
-                assert_eq!(elems.len(), size);
-                (elems, blinding)
+                Ok((elems, blinding))
             }
         }
     }
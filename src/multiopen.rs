@@ -0,0 +1,329 @@
+use crate::{
+    challenges::ChallengeGenerator, transcript::Transcript, Commitment, Fr, IpaError, IpaScheme,
+    Opening, VerifierKey,
+};
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, ModelParameters, SWModelParameters};
+use ark_ff::{Field, One, Zero};
+use ark_poly::{
+    univariate::{DenseOrSparsePolynomial, DensePolynomial},
+    Polynomial, UVPolynomial,
+};
+use rand::Rng;
+use std::iter::successors;
+
+///openings of polynomials at sets of points reduced to a single opening.
+///With `f_i` opened at `S_i` and `r_i` interpolating its evaluations, the
+///quotient is `h = sum g^i (f_i - r_i) / Z_{S_i}` and the opening is of
+///`sum g^i (f_i - r_i(x)) / Z_{S_i}(x) - h`, which is zero at the challenge `x`
+#[derive(Debug, Clone)]
+pub struct MultiPointOpening<P: SWModelParameters> {
+    pub(crate) evals: Vec<Vec<Fr<P>>>,
+    pub(crate) quotient: GroupAffine<P>,
+    pub(crate) opening: Opening<P>,
+}
+
+impl<P: SWModelParameters> MultiPointOpening<P> {
+    ///claimed evaluations, one vector per commitment in the order of its points
+    pub fn evals(&self) -> &[Vec<Fr<P>>] {
+        &self.evals
+    }
+}
+
+impl<P, R, T> IpaScheme<P, R, T>
+where
+    P: ModelParameters + SWModelParameters,
+    Fr<P>: One,
+    R: Rng,
+    T: Transcript<P>,
+{
+    ///opens each polynomial at its set of points with a single opening, the
+    ///proof has the evaluations, the quotient commitment and one opening
+    #[allow(clippy::type_complexity)]
+    pub fn open_multi_point(
+        &self,
+        opens: Vec<(Commitment<P, false>, &[Fr<P>], Vec<Fr<P>>)>,
+    ) -> Result<MultiPointOpening<P>, IpaError> {
+        if opens.is_empty() {
+            return Err(IpaError::EmptyBatch);
+        }
+        let mut transcript = ChallengeGenerator::<P, T>::new_multi_point();
+        let len = opens.len();
+        let (mut commitments, mut polys) = (Vec::with_capacity(len), Vec::with_capacity(len));
+        let (mut sets, mut evals) = (Vec::with_capacity(len), Vec::with_capacity(len));
+        for (commitment, coeffs, points) in opens {
+            self.check_degree(coeffs.len())?;
+            check_points::<P>(&points)?;
+            let poly = DensePolynomial::from_coefficients_slice(coeffs);
+            let poly_evals = points
+                .iter()
+                .map(|point| poly.evaluate(point))
+                .collect::<Vec<_>>();
+            transcript.digest_point_set(&commitment, &points, &poly_evals);
+            commitments.push(commitment);
+            polys.push(poly);
+            sets.push(points);
+            evals.push(poly_evals);
+        }
+        let element = transcript.multi_point_element();
+        let combinations = successors(Some(Fr::<P>::one()), |e| Some(*e * element));
+
+        let mut quotient = DensePolynomial::<Fr<P>>::zero();
+        for ((poly, points), combination) in polys.iter().zip(&sets).zip(combinations) {
+            let vanishing = vanishing_poly::<P>(points);
+            let (q, _) = DenseOrSparsePolynomial::from(poly)
+                .divide_with_q_and_r(&vanishing.into())
+                .ok_or(IpaError::EvaluationMismatch)?;
+            quotient += (combination, &q);
+        }
+        let Commitment::<P, false>(quotient_commitment) = self.commit(quotient.clone())?;
+        let point = transcript.quotient_challenge(&quotient_commitment);
+
+        let claims = sets.iter().zip(&evals);
+        let (factors, constant) = linearization_factors::<P>(claims, element, point)?;
+        let mut coeffs = vec![Fr::<P>::zero(); self.vk.max_degree];
+        for (poly, factor) in polys.into_iter().zip(&factors) {
+            for (coeff, e) in coeffs.iter_mut().zip(poly.coeffs) {
+                *coeff += e * *factor;
+            }
+        }
+        coeffs[0] -= constant;
+        for (coeff, e) in coeffs.iter_mut().zip(quotient.coeffs) {
+            *coeff -= e;
+        }
+        let commitment = linearized_commitment(
+            self.vk.basis[0],
+            commitments,
+            &factors,
+            constant,
+            Commitment(quotient_commitment),
+        );
+        let opening = self.open(commitment, &coeffs, point, Fr::<P>::zero())?;
+        Ok(MultiPointOpening {
+            evals,
+            quotient: quotient_commitment,
+            opening,
+        })
+    }
+    ///same as verifying with the `VerifierKey` of the scheme
+    pub fn verify_multi_point(
+        &self,
+        claims: &[(Commitment<P, false>, Vec<Fr<P>>)],
+        multi_point: MultiPointOpening<P>,
+    ) -> Result<Vec<Vec<Fr<P>>>, IpaError>
+    where
+        Commitment<P, false>: Copy,
+    {
+        self.vk.verify_multi_point(claims, multi_point)
+    }
+}
+
+impl<P, T> VerifierKey<P, T>
+where
+    P: ModelParameters + SWModelParameters,
+    Fr<P>: One,
+    T: Transcript<P>,
+{
+    ///checks the openings of the commitments at their sets of points, returning
+    ///the evaluations in the same order
+    pub fn verify_multi_point(
+        &self,
+        claims: &[(Commitment<P, false>, Vec<Fr<P>>)],
+        multi_point: MultiPointOpening<P>,
+    ) -> Result<Vec<Vec<Fr<P>>>, IpaError>
+    where
+        Commitment<P, false>: Copy,
+    {
+        let MultiPointOpening {
+            evals,
+            quotient,
+            opening,
+        } = multi_point;
+        if claims.len() != evals.len() {
+            return Err(IpaError::LengthMismatch {
+                expected: claims.len(),
+                found: evals.len(),
+            });
+        }
+        if claims.is_empty() {
+            return Err(IpaError::EmptyBatch);
+        }
+        let mut transcript = ChallengeGenerator::<P, T>::new_multi_point();
+        for ((commitment, points), evals) in claims.iter().zip(&evals) {
+            check_points::<P>(points)?;
+            if points.len() != evals.len() {
+                return Err(IpaError::LengthMismatch {
+                    expected: points.len(),
+                    found: evals.len(),
+                });
+            }
+            transcript.digest_point_set(commitment, points, evals);
+        }
+        let element = transcript.multi_point_element();
+        let point = transcript.quotient_challenge(&quotient);
+        if opening.point != point || !opening.eval.is_zero() {
+            return Err(IpaError::BatchMismatch);
+        }
+
+        let sets = claims.iter().map(|(_, points)| points).zip(&evals);
+        let (factors, constant) = linearization_factors::<P>(sets, element, point)?;
+        let commitments = claims.iter().map(|(commitment, _)| *commitment);
+        let commitment = linearized_commitment(
+            self.basis[0],
+            commitments,
+            &factors,
+            constant,
+            Commitment(quotient),
+        );
+        self.verify(commitment, opening)?;
+        Ok(evals)
+    }
+}
+
+///sets must be non empty and without repeated points
+fn check_points<P: SWModelParameters>(points: &[Fr<P>]) -> Result<(), IpaError> {
+    if points.is_empty() {
+        return Err(IpaError::EmptyBatch);
+    }
+    for (i, point) in points.iter().enumerate() {
+        if points[..i].contains(point) {
+            return Err(IpaError::RepeatedPoint);
+        }
+    }
+    Ok(())
+}
+
+///the polynomial that is zero at all the points
+fn vanishing_poly<P: SWModelParameters>(points: &[Fr<P>]) -> DensePolynomial<Fr<P>> {
+    points.iter().fold(
+        DensePolynomial::from_coefficients_vec(vec![Fr::<P>::one()]),
+        |acc, point| &acc * &DensePolynomial::from_coefficients_vec(vec![-*point, Fr::<P>::one()]),
+    )
+}
+
+///evaluates at `x` the polynomial interpolating the evaluations at the points
+fn interpolate_at<P: SWModelParameters>(
+    points: &[Fr<P>],
+    evals: &[Fr<P>],
+    x: Fr<P>,
+) -> Result<Fr<P>, IpaError> {
+    let mut result = Fr::<P>::zero();
+    for (j, (point_j, eval)) in points.iter().zip(evals).enumerate() {
+        let mut numerator = Fr::<P>::one();
+        let mut denominator = Fr::<P>::one();
+        for (k, point_k) in points.iter().enumerate() {
+            if k != j {
+                numerator *= x - point_k;
+                denominator *= *point_j - point_k;
+            }
+        }
+        let denominator = denominator.inverse().ok_or(IpaError::RepeatedPoint)?;
+        result += *eval * numerator * denominator;
+    }
+    Ok(result)
+}
+
+///the factor of each polynomial, `g^i / Z_{S_i}(x)`, and the constant term
+///`sum g^i r_i(x) / Z_{S_i}(x)` of the opened polynomial
+fn linearization_factors<'a, P: SWModelParameters>(
+    claims: impl Iterator<Item = (&'a Vec<Fr<P>>, &'a Vec<Fr<P>>)>,
+    element: Fr<P>,
+    x: Fr<P>,
+) -> Result<(Vec<Fr<P>>, Fr<P>), IpaError> {
+    let combinations = successors(Some(Fr::<P>::one()), |e| Some(*e * element));
+    let mut factors = vec![];
+    let mut constant = Fr::<P>::zero();
+    for ((points, evals), combination) in claims.zip(combinations) {
+        let vanishing = points
+            .iter()
+            .fold(Fr::<P>::one(), |acc, point| acc * (x - point));
+        let factor = combination * vanishing.inverse().ok_or(IpaError::ZeroChallenge)?;
+        constant += factor * interpolate_at::<P>(points, evals, x)?;
+        factors.push(factor);
+    }
+    Ok((factors, constant))
+}
+
+///the commitment to `sum factor_i f_i - constant - h`, with the homomorphism
+fn linearized_commitment<P: SWModelParameters>(
+    first_basis: GroupAffine<P>,
+    commitments: impl IntoIterator<Item = Commitment<P, false>>,
+    factors: &[Fr<P>],
+    constant: Fr<P>,
+    quotient: Commitment<P, false>,
+) -> Commitment<P, false> {
+    let correction = Commitment(first_basis) * constant + quotient;
+    commitments
+        .into_iter()
+        .zip(factors)
+        .fold(-correction, |acc, (commitment, factor)| {
+            acc + commitment * *factor
+        })
+}
+
+#[test]
+fn multi_point() {
+    use crate::{tests::commit_and_open, Init};
+    use ark_pallas::PallasParameters;
+    use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use rand::thread_rng;
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, false, thread_rng()).unwrap();
+    let omega = Radix2EvaluationDomain::<Fr<PallasParameters>>::new(256)
+        .unwrap()
+        .group_gen;
+    let z = Fr::<PallasParameters>::from(7);
+    //rotations of the arithmetization: z, wz and w^-1 z
+    let sets = [
+        vec![z, omega * z, omega.inverse().unwrap() * z],
+        vec![z],
+        vec![omega * z, z],
+    ];
+    let polys = sets
+        .iter()
+        .map(|_| commit_and_open(&scheme))
+        .collect::<Vec<_>>();
+    let opens = polys
+        .iter()
+        .zip(sets.clone())
+        .map(|((commit, poly, ..), points)| (*commit, &**poly, points))
+        .collect::<Vec<_>>();
+    let claims = polys
+        .iter()
+        .zip(sets.clone())
+        .map(|((commit, ..), points)| (*commit, points))
+        .collect::<Vec<_>>();
+
+    let proof = scheme.open_multi_point(opens.clone()).unwrap();
+    assert_eq!(proof.opening.rounds.len(), 8);
+    let mut bytes = vec![];
+    proof.serialize(&mut bytes).unwrap();
+    let proof = MultiPointOpening::deserialize(&*bytes).unwrap();
+    let evals = scheme.verify_multi_point(&claims, proof.clone()).unwrap();
+    for ((_, poly, ..), (points, evals)) in polys.iter().zip(sets.iter().zip(evals)) {
+        let poly = DensePolynomial::from_coefficients_slice(poly);
+        let expected = points.iter().map(|p| poly.evaluate(p)).collect::<Vec<_>>();
+        assert_eq!(evals, expected);
+    }
+
+    let mut wrong = proof.clone();
+    wrong.evals[0][1] += Fr::<PallasParameters>::one();
+    assert_eq!(
+        scheme.verify_multi_point(&claims, wrong).err(),
+        Some(IpaError::BatchMismatch)
+    );
+    let mut moved = claims.clone();
+    moved[1].1 = vec![omega * z];
+    assert!(scheme.verify_multi_point(&moved, proof.clone()).is_err());
+    let mut swapped = claims.clone();
+    swapped.swap(0, 1);
+    assert!(scheme.verify_multi_point(&swapped, proof).is_err());
+
+    let mut repeated = opens;
+    repeated[1].2 = vec![z, z];
+    assert!(matches!(
+        scheme.open_multi_point(repeated),
+        Err(IpaError::RepeatedPoint)
+    ));
+}
@@ -1,117 +1,251 @@
 use crate::{
-    challenges::ChallengeGenerator, commit::CommitmentTrait, Commitment, Fr, HidingOpening,
-    IpaScheme, Opening, UnsafeHidingCommitment,
+    challenges::ChallengeGenerator,
+    commit::CommitmentTrait,
+    transcript::{Blake3Transcript, Transcript},
+    utils::eval_b_poly,
+    Commitment, Fr, HidingOpening, IpaError, IpaScheme, Opening, UnsafeHidingCommitment,
+    VerifierKey,
 };
-use ark_ec::{AffineCurve, SWModelParameters};
-use rand::Rng;
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, AffineCurve, ProjectiveCurve, SWModelParameters,
+};
+use ark_ff::One;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-pub trait OpenTrait<P, R>
+pub trait OpenTrait<P, R, T = Blake3Transcript>
 where
     P: SWModelParameters,
-    Self::Commit: CommitmentTrait<P, R>,
+    Self: Sized,
+    Self::Commit: CommitmentTrait<P, R, T>,
     R: Rng,
+    T: Transcript<P>,
 {
-    type Commit: CommitmentTrait<P, R>;
+    type Commit: CommitmentTrait<P, R, T>;
 
     fn open(
-        scheme: &IpaScheme<P, R>,
+        scheme: &IpaScheme<P, R, T>,
         commitment: Self::Commit,
         coeffs: &[Fr<P>],
         point: Fr<P>,
         eval: Fr<P>,
-    ) -> Self;
+    ) -> Result<Self, IpaError>;
 }
 
-impl<P, R> OpenTrait<P, R> for Opening<P>
+impl<P, R, T> OpenTrait<P, R, T> for Opening<P>
 where
     P: SWModelParameters,
     R: Rng,
+    T: Transcript<P>,
 {
     type Commit = Commitment<P, false>;
 
     fn open(
-        scheme: &IpaScheme<P, R>,
+        scheme: &IpaScheme<P, R, T>,
         commitment: Self::Commit,
         coeffs: &[Fr<P>],
         point: Fr<P>,
         eval: Fr<P>,
-    ) -> Self {
-        let u = ChallengeGenerator::inner_product_basis(&commitment, &point);
-        let basis = &*scheme.basis;
-        let b = scheme.b(point);
-        //let mut rng = &self.rng;
-        let first = IpaScheme::<P, R>::round(basis, coeffs, &*b, u, None);
-        let rounds = vec![(first.lj, first.rj)];
-        let (opening, _, _) = IpaScheme::<P, R>::open_recursive(first, rounds, point, eval, u);
-        opening
+    ) -> Result<Self, IpaError> {
+        let (opening, _) = open_with_basis(scheme, commitment, coeffs, point, eval)?;
+        Ok(opening)
     }
 }
 
-impl<P, R> OpenTrait<P, R> for HidingOpening<P>
+///the non hiding opening along with the final basis it reduces to
+pub(crate) fn open_with_basis<P, R, T>(
+    scheme: &IpaScheme<P, R, T>,
+    commitment: Commitment<P, false>,
+    coeffs: &[Fr<P>],
+    point: Fr<P>,
+    eval: Fr<P>,
+) -> Result<(Opening<P>, GroupAffine<P>), IpaError>
 where
     P: SWModelParameters,
     R: Rng,
+    T: Transcript<P>,
+{
+    let b = scheme.b(point);
+    let coeffs = &scheme.prepare_opening(coeffs, &b, eval)?;
+    let mut transcript = ChallengeGenerator::new_opening(&commitment, &point, &eval);
+    let u = transcript.inner_product_basis();
+    let basis = &*scheme.vk.basis;
+    let first =
+        IpaScheme::<P, R, T>::round(basis, Fr::<P>::one(), coeffs, &b, u, None, &mut transcript)?;
+    let rounds = vec![(first.lj, first.rj)];
+    let (opening, _, final_basis) =
+        IpaScheme::<P, R, T>::open_recursive(first, rounds, point, eval, u, &mut transcript)?;
+    Ok((opening, final_basis))
+}
+
+impl<P, R, T> OpenTrait<P, R, T> for HidingOpening<P>
+where
+    P: SWModelParameters,
+    R: Rng,
+    T: Transcript<P>,
 {
     type Commit = UnsafeHidingCommitment<P>;
 
     fn open(
-        scheme: &IpaScheme<P, R>,
+        scheme: &IpaScheme<P, R, T>,
         commitment: Self::Commit,
         coeffs: &[Fr<P>],
         point: Fr<P>,
         eval: Fr<P>,
-    ) -> Self {
-        //let rng = rng.unwrap();
-        let rng = { &mut *scheme.rng.borrow_mut() };
-        let UnsafeHidingCommitment(commitment, blinding) = commitment;
-        let commitment = Commitment::<_, true>(commitment);
-        let u = ChallengeGenerator::inner_product_basis(&commitment, &point);
-        let basis = &*scheme.basis;
-        let blinding_basis = scheme.blinding_basis;
+    ) -> Result<Self, IpaError> {
         let b = scheme.b(point);
-        //let mut rng = &self.rng;
-        let first = IpaScheme::<P, R>::hiding_round(
-            basis,
+        let coeffs = &scheme.prepare_opening(coeffs, &b, eval)?;
+        //seeded from the scheme rng, so the lock isn't held during the opening
+        let rng = &mut StdRng::from_seed(scheme.lock_rng().gen());
+        let (opening, ..) = IpaScheme::<P, R, T>::open_hiding_prepared(
+            &scheme.vk.basis,
+            scheme.vk.blinding_basis,
+            commitment,
             coeffs,
-            &*b,
-            u,
-            blinding_basis,
-            rng,
-            blinding,
-            None,
-        );
-        let rounds = vec![(first.lj, first.rj)];
-        let opening = IpaScheme::<P, R>::open_recursive_hiding(
-            first,
-            rounds,
+            &b,
             point,
             eval,
-            u,
-            blinding_basis,
             rng,
-        );
-        opening
+            None,
+        )?;
+        Ok(opening)
     }
 }
 
-pub trait VerifTrait<P, R>
+pub trait VerifTrait<P, T = Blake3Transcript>
 where
     P: SWModelParameters,
-    R: Rng,
+    T: Transcript<P>,
 {
     type Commit;
 
-    fn verify(self, scheme: &IpaScheme<P, R>, commitment: Self::Commit) -> Option<Fr<P>>;
+    fn verify(self, key: &VerifierKey<P, T>, commitment: Self::Commit) -> Result<Fr<P>, IpaError>;
 }
 
-impl<P, R> VerifTrait<P, R> for Opening<P>
+///openings checked against a claimed final basis, leaving the basis to be
+///checked by the amortization
+pub trait AmortizedVerifTrait<P, T = Blake3Transcript>: VerifTrait<P, T>
 where
     P: SWModelParameters,
-    R: Rng,
+    T: Transcript<P>,
+{
+    ///digests the commitment with the statement and the messages of the
+    ///opening, so the amortization depends on all of them
+    fn digest_for_amortization(
+        &self,
+        commitment: &Self::Commit,
+        transcript: &mut ChallengeGenerator<P, T>,
+    );
+    ///returns the evaluation and the challenges of the rounds
+    #[allow(clippy::type_complexity)]
+    fn verify_with_basis(
+        self,
+        key: &VerifierKey<P, T>,
+        commitment: &Self::Commit,
+        final_basis: GroupAffine<P>,
+    ) -> Result<(Fr<P>, Vec<(Fr<P>, Fr<P>)>), IpaError>;
+}
+
+impl<P, T> AmortizedVerifTrait<P, T> for Opening<P>
+where
+    P: SWModelParameters,
+    T: Transcript<P>,
+{
+    fn digest_for_amortization(
+        &self,
+        commitment: &Self::Commit,
+        transcript: &mut ChallengeGenerator<P, T>,
+    ) {
+        let Opening {
+            point,
+            eval,
+            rounds,
+            a,
+        } = self;
+        transcript.digest_opening(commitment, point, eval, rounds, &[], &[*a]);
+    }
+    fn verify_with_basis(
+        self,
+        key: &VerifierKey<P, T>,
+        commitment: &Self::Commit,
+        final_basis: GroupAffine<P>,
+    ) -> Result<(Fr<P>, Vec<(Fr<P>, Fr<P>)>), IpaError> {
+        let Opening {
+            point,
+            eval,
+            rounds,
+            a,
+        } = self;
+        key.check_round_count(rounds.len())?;
+        let mut transcript = ChallengeGenerator::<P, T>::new_opening(commitment, &point, &eval);
+        let u = transcript.inner_product_basis();
+        let (final_commit, challenges) =
+            VerifierKey::process_rounds(commitment, eval, &rounds, u, &mut transcript)?;
+        let b = eval_b_poly::<P>(&challenges, point);
+        match final_commit == final_basis.mul(a) + u.mul(a * b) {
+            true => Ok((eval, challenges)),
+            false => Err(IpaError::FinalCheckFailed),
+        }
+    }
+}
+
+impl<P, T> AmortizedVerifTrait<P, T> for HidingOpening<P>
+where
+    P: SWModelParameters,
+    T: Transcript<P>,
+{
+    fn digest_for_amortization(
+        &self,
+        commitment: &Self::Commit,
+        transcript: &mut ChallengeGenerator<P, T>,
+    ) {
+        let HidingOpening {
+            point,
+            eval,
+            rounds,
+            r,
+            z1,
+            z2,
+        } = self;
+        transcript.digest_opening(commitment, point, eval, rounds, &[*r], &[*z1, *z2]);
+    }
+    fn verify_with_basis(
+        self,
+        key: &VerifierKey<P, T>,
+        commitment: &Self::Commit,
+        final_basis: GroupAffine<P>,
+    ) -> Result<(Fr<P>, Vec<(Fr<P>, Fr<P>)>), IpaError> {
+        let HidingOpening {
+            point,
+            eval,
+            rounds,
+            r,
+            z1,
+            z2,
+        } = self;
+        key.check_round_count(rounds.len())?;
+        let mut transcript = ChallengeGenerator::<P, T>::new_opening(commitment, &point, &eval);
+        let u = transcript.inner_product_basis();
+        let (final_commit, challenges) =
+            VerifierKey::process_rounds(commitment, eval, &rounds, u, &mut transcript)?;
+        let b = eval_b_poly::<P>(&challenges, point);
+        let c = transcript.sigma_challenge(&r);
+        let lhs = final_commit.into_affine().mul(c) + r.into_projective();
+        let combined = final_basis.into_projective() + u.mul(b);
+        match lhs == combined.into_affine().mul(z1) + key.blinding_basis.mul(z2) {
+            true => Ok((eval, challenges)),
+            false => Err(IpaError::FinalCheckFailed),
+        }
+    }
+}
+
+impl<P, T> VerifTrait<P, T> for Opening<P>
+where
+    P: SWModelParameters,
+    T: Transcript<P>,
 {
     type Commit = Commitment<P, false>;
 
-    fn verify(self, scheme: &IpaScheme<P, R>, commitment: Self::Commit) -> Option<Fr<P>> {
+    fn verify(self, key: &VerifierKey<P, T>, commitment: Self::Commit) -> Result<Fr<P>, IpaError> {
         let open = self;
         let Opening::<P> {
             point,
@@ -119,36 +253,40 @@ where
             a,
             rounds,
         } = open;
-        let (final_commit, check) = scheme.general_verify(commitment, point, eval, a, rounds);
-        if final_commit == check {
-            Some(eval)
+        let (final_commit, final_basis, _) = key.general_verify(commitment, point, eval, rounds)?;
+        if final_commit == final_basis.mul(a) {
+            Ok(eval)
         } else {
-            None
+            Err(IpaError::FinalCheckFailed)
         }
     }
 }
 
-impl<P, R> VerifTrait<P, R> for HidingOpening<P>
+impl<P, T> VerifTrait<P, T> for HidingOpening<P>
 where
     P: SWModelParameters,
-    R: Rng,
+    T: Transcript<P>,
 {
     type Commit = Commitment<P, true>;
 
-    fn verify(self, scheme: &IpaScheme<P, R>, commitment: Self::Commit) -> Option<Fr<P>> {
+    fn verify(self, key: &VerifierKey<P, T>, commitment: Self::Commit) -> Result<Fr<P>, IpaError> {
         let open = self;
         let HidingOpening::<P> {
             point,
             eval,
-            a,
             rounds,
-            blinding_factor,
+            r,
+            z1,
+            z2,
         } = open;
-        let (final_commit, check) = scheme.general_verify(commitment, point, eval, a, rounds);
-        if final_commit == check + scheme.blinding_basis.mul(blinding_factor) {
-            Some(eval)
+        let (final_commit, final_basis, mut transcript) =
+            key.general_verify(commitment, point, eval, rounds)?;
+        let c = transcript.sigma_challenge(&r);
+        let lhs = final_commit.into_affine().mul(c) + r.into_projective();
+        if lhs == final_basis.mul(z1) + key.blinding_basis.mul(z2) {
+            Ok(eval)
         } else {
-            None
+            Err(IpaError::FinalCheckFailed)
         }
     }
 }
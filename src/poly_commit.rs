@@ -0,0 +1,972 @@
+//!adapter implementing `ark_poly_commit::PolynomialCommitment` over `IpaScheme`
+use crate::{
+    amortization::MultiOpening,
+    transcript::{Blake3Transcript, Transcript},
+    Blinding, Commitment, Fr, HidingOpening, Init, IpaError, IpaScheme, Opening,
+    UnsafeHidingCommitment, VerifierKey,
+};
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, AffineCurve, ProjectiveCurve, SWModelParameters,
+};
+use ark_ff::{One, ToBytes, UniformRand, Zero};
+use ark_poly::{univariate::DensePolynomial, Polynomial};
+use ark_poly_commit::{
+    BatchLCProof, Error, Evaluations, LCTerm, LabeledCommitment, LabeledPolynomial,
+    LinearCombination, PCCommitment, PCCommitterKey, PCPreparedCommitment, PCPreparedVerifierKey,
+    PCProof, PCRandomness, PCUniversalParams, PCVerifierKey, PolynomialCommitment, QuerySet,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Debug, Display, Formatter},
+    marker::PhantomData,
+    sync::Mutex,
+};
+
+///`PolynomialCommitment` for univariate polynomials over the scalar field of
+///`P`. Degree bounds are not supported; hiding commitments blind with the
+///rng given to `commit`, the openings take their randomness from the key
+pub struct IpaPC<P: SWModelParameters + Copy, T: Transcript<P> = Blake3Transcript>(
+    PhantomData<(P, T)>,
+);
+
+type Poly<P> = DensePolynomial<Fr<P>>;
+
+///universal parameters and committer key at once, trimming only takes a
+///prefix of the basis
+pub struct IpaKey<P: SWModelParameters + Copy, T: Transcript<P> = Blake3Transcript> {
+    scheme: IpaScheme<P, StdRng, T>,
+    ///maximum degree of the parameters the key was trimmed from
+    max_degree: usize,
+}
+
+///verifier key and prepared verifier key, the `VerifierKey` of the trimmed
+///scheme without its rng
+pub struct IpaVerifierKey<P: SWModelParameters + Copy, T: Transcript<P> = Blake3Transcript> {
+    vk: VerifierKey<P, T>,
+    ///maximum degree of the parameters the key was trimmed from
+    max_degree: usize,
+}
+
+#[derive(Clone, Debug)]
+pub enum IpaProof<P: SWModelParameters + Copy> {
+    Opening(Opening<P>),
+    Hiding(HidingOpening<P>),
+}
+
+///one proof per point, amortized when none of the polynomials is hiding
+#[derive(Clone)]
+pub enum IpaBatchProof<P: SWModelParameters + Copy> {
+    Individual(Vec<IpaProof<P>>),
+    Amortized(MultiOpening<P>),
+}
+
+#[derive(Debug)]
+pub enum IpaPCError {
+    Ipa(IpaError),
+    PolyCommit(Error),
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> IpaKey<P, T> {
+    fn new(scheme: IpaScheme<P, StdRng, T>, max_degree: usize) -> Self {
+        Self { scheme, max_degree }
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> Clone for IpaKey<P, T> {
+    ///the copy gets its own rng, derived from the original one
+    fn clone(&self) -> Self {
+        let rng = StdRng::from_seed(self.scheme.lock_rng().gen());
+        let scheme = IpaScheme {
+            vk: self.scheme.vk.clone(),
+            evaluation_basis: self.scheme.evaluation_basis.clone(),
+            rng: Mutex::new(rng),
+        };
+        Self::new(scheme, self.max_degree)
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> Debug for IpaKey<P, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IpaKey")
+            .field("max_degree", &self.max_degree)
+            .field("supported_degree", &PCCommitterKey::supported_degree(self))
+            .finish()
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> CanonicalSerialize for IpaKey<P, T> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.max_degree.serialize(&mut writer)?;
+        self.scheme.vk.serialize(&mut writer)
+    }
+    fn serialized_size(&self) -> usize {
+        self.max_degree.serialized_size() + self.scheme.vk.serialized_size()
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> CanonicalDeserialize for IpaKey<P, T> {
+    ///the key gets a fresh rng
+    fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        let (max_degree, vk) = read_key(reader)?;
+        let scheme = IpaScheme::from_verifier_key(vk, false, StdRng::from_entropy())
+            .map_err(|_| SerializationError::InvalidData)?;
+        Ok(Self::new(scheme, max_degree))
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> PCUniversalParams for IpaKey<P, T> {
+    fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> PCCommitterKey for IpaKey<P, T> {
+    fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+    fn supported_degree(&self) -> usize {
+        self.scheme.vk.max_degree - 1
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> Clone for IpaVerifierKey<P, T> {
+    fn clone(&self) -> Self {
+        Self {
+            vk: self.vk.clone(),
+            max_degree: self.max_degree,
+        }
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> Debug for IpaVerifierKey<P, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IpaVerifierKey")
+            .field("max_degree", &self.max_degree)
+            .field("supported_degree", &PCVerifierKey::supported_degree(self))
+            .finish()
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> CanonicalSerialize for IpaVerifierKey<P, T> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.max_degree.serialize(&mut writer)?;
+        self.vk.serialize(&mut writer)
+    }
+    fn serialized_size(&self) -> usize {
+        self.max_degree.serialized_size() + self.vk.serialized_size()
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> CanonicalDeserialize for IpaVerifierKey<P, T> {
+    fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        let (max_degree, vk) = read_key(reader)?;
+        Ok(Self { vk, max_degree })
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> PCVerifierKey for IpaVerifierKey<P, T> {
+    fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+    fn supported_degree(&self) -> usize {
+        self.vk.max_degree - 1
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> PCPreparedVerifierKey<IpaVerifierKey<P, T>>
+    for IpaVerifierKey<P, T>
+{
+    fn prepare(vk: &IpaVerifierKey<P, T>) -> Self {
+        vk.clone()
+    }
+}
+
+impl<P: SWModelParameters + Copy> Default for Commitment<P, false> {
+    fn default() -> Self {
+        Commitment(GroupAffine::zero())
+    }
+}
+
+impl<P: SWModelParameters + Copy> ToBytes for Commitment<P, false> {
+    fn write<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        self.0.write(writer)
+    }
+}
+
+impl<P: SWModelParameters + Copy> PCCommitment for Commitment<P, false> {
+    fn empty() -> Self {
+        Self::default()
+    }
+    fn has_degree_bound(&self) -> bool {
+        false
+    }
+    fn size_in_bytes(&self) -> usize {
+        self.serialized_size()
+    }
+}
+
+impl<P: SWModelParameters + Copy> PCPreparedCommitment<Commitment<P, false>>
+    for Commitment<P, false>
+{
+    fn prepare(comm: &Commitment<P, false>) -> Self {
+        *comm
+    }
+}
+
+impl<P: SWModelParameters + Copy> CanonicalSerialize for Blinding<P> {
+    fn serialize<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        self.0.serialize(writer)
+    }
+    fn serialized_size(&self) -> usize {
+        self.0.serialized_size()
+    }
+}
+
+impl<P: SWModelParameters + Copy> CanonicalDeserialize for Blinding<P> {
+    fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        Ok(Self(Fr::<P>::deserialize(reader)?))
+    }
+}
+
+impl<P: SWModelParameters + Copy> PCRandomness for Blinding<P> {
+    fn empty() -> Self {
+        Self(Fr::<P>::zero())
+    }
+    fn rand<R: RngCore>(_: usize, _: bool, _: Option<usize>, rng: &mut R) -> Self {
+        Self(Fr::<P>::rand(rng))
+    }
+}
+
+impl<P: SWModelParameters + Copy> CanonicalSerialize for IpaProof<P> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        match self {
+            IpaProof::Opening(opening) => {
+                0_u8.serialize(&mut writer)?;
+                opening.serialize(writer)
+            }
+            IpaProof::Hiding(opening) => {
+                1_u8.serialize(&mut writer)?;
+                opening.serialize(writer)
+            }
+        }
+    }
+    fn serialized_size(&self) -> usize {
+        1 + match self {
+            IpaProof::Opening(opening) => opening.serialized_size(),
+            IpaProof::Hiding(opening) => opening.serialized_size(),
+        }
+    }
+}
+
+impl<P: SWModelParameters + Copy> CanonicalDeserialize for IpaProof<P> {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize(&mut reader)? {
+            0 => Ok(IpaProof::Opening(Opening::deserialize(reader)?)),
+            1 => Ok(IpaProof::Hiding(HidingOpening::deserialize(reader)?)),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+impl<P: SWModelParameters + Copy> ToBytes for IpaProof<P> {
+    fn write<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        self.serialize(writer).map_err(std::io::Error::other)
+    }
+}
+
+impl<P: SWModelParameters + Copy> PCProof for IpaProof<P> {
+    fn size_in_bytes(&self) -> usize {
+        self.serialized_size()
+    }
+}
+
+impl<P: SWModelParameters + Copy> CanonicalSerialize for IpaBatchProof<P> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        match self {
+            IpaBatchProof::Individual(proofs) => {
+                0_u8.serialize(&mut writer)?;
+                proofs.serialize(writer)
+            }
+            IpaBatchProof::Amortized(multi) => {
+                1_u8.serialize(&mut writer)?;
+                multi.serialize(writer)
+            }
+        }
+    }
+    fn serialized_size(&self) -> usize {
+        1 + match self {
+            IpaBatchProof::Individual(proofs) => proofs.serialized_size(),
+            IpaBatchProof::Amortized(multi) => multi.serialized_size(),
+        }
+    }
+}
+
+impl<P: SWModelParameters + Copy> CanonicalDeserialize for IpaBatchProof<P> {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize(&mut reader)? {
+            0 => Ok(IpaBatchProof::Individual(Vec::deserialize(reader)?)),
+            1 => Ok(IpaBatchProof::Amortized(MultiOpening::deserialize(reader)?)),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+impl<P: SWModelParameters + Copy> From<Vec<IpaProof<P>>> for IpaBatchProof<P> {
+    fn from(proofs: Vec<IpaProof<P>>) -> Self {
+        IpaBatchProof::Individual(proofs)
+    }
+}
+
+///the openings of an amortized proof are complete proofs by themselves
+impl<P: SWModelParameters + Copy> From<IpaBatchProof<P>> for Vec<IpaProof<P>> {
+    fn from(proof: IpaBatchProof<P>) -> Self {
+        match proof {
+            IpaBatchProof::Individual(proofs) => proofs,
+            IpaBatchProof::Amortized(multi) => multi
+                .openings
+                .into_iter()
+                .map(|(opening, _)| IpaProof::Opening(opening))
+                .collect(),
+        }
+    }
+}
+
+impl Display for IpaPCError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpaPCError::Ipa(err) => Display::fmt(err, f),
+            IpaPCError::PolyCommit(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl std::error::Error for IpaPCError {}
+
+impl From<IpaError> for IpaPCError {
+    fn from(err: IpaError) -> Self {
+        IpaPCError::Ipa(err)
+    }
+}
+
+impl From<Error> for IpaPCError {
+    fn from(err: Error) -> Self {
+        IpaPCError::PolyCommit(err)
+    }
+}
+
+///the linear combination of several polynomials opened at the same point
+struct Combination<P: SWModelParameters + Copy> {
+    poly: Poly<P>,
+    commitment: Commitment<P, false>,
+    blinding: Fr<P>,
+    hiding: bool,
+}
+
+///number of rounds needed for polynomials up to `degree`
+fn log_size(degree: usize) -> Result<u8, IpaPCError> {
+    let size = degree
+        .checked_add(1)
+        .and_then(usize::checked_next_power_of_two)
+        .ok_or(Error::TrimmingDegreeTooLarge)?;
+    Ok(size.trailing_zeros().max(1) as u8)
+}
+
+///reads the maximum degree and the basis of a key, the basis has to be a power
+///of two no bigger than the one of the parameters it was trimmed from
+fn read_key<P: SWModelParameters, T: Transcript<P>, R: Read>(
+    mut reader: R,
+) -> Result<(usize, VerifierKey<P, T>), SerializationError> {
+    let max_degree = usize::deserialize(&mut reader)?;
+    let vk = VerifierKey::<P, T>::deserialize(&mut reader)?;
+    let max_log_size = log_size(max_degree).map_err(|_| SerializationError::InvalidData)?;
+    match vk.max_degree.trailing_zeros() > max_log_size as u32 {
+        true => Err(SerializationError::InvalidData),
+        false => Ok((max_degree, vk)),
+    }
+}
+
+fn no_degree_bound(bound: Option<usize>) -> Result<(), IpaPCError> {
+    match bound {
+        Some(bound) => Err(Error::UnsupportedDegreeBound(bound).into()),
+        None => Ok(()),
+    }
+}
+
+impl<P: SWModelParameters + Copy, T: Transcript<P>> IpaPC<P, T> {
+    fn combine<'a>(
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<Fr<P>, Poly<P>>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Commitment<P, false>>>,
+        rands: impl IntoIterator<Item = &'a Blinding<P>>,
+        opening_challenges: &dyn Fn(u64) -> Fr<P>,
+    ) -> Result<Combination<P>, IpaPCError> {
+        let mut combination = Combination {
+            poly: Poly::<P>::zero(),
+            commitment: Commitment::default(),
+            blinding: Fr::<P>::zero(),
+            hiding: false,
+        };
+        let terms = polynomials.into_iter().zip(commitments).zip(rands);
+        for (i, ((poly, commitment), blinding)) in terms.enumerate() {
+            no_degree_bound(poly.degree_bound())?;
+            let challenge = opening_challenges(i as u64);
+            combination.poly += (challenge, poly.polynomial());
+            combination.commitment = combination.commitment + *commitment.commitment() * challenge;
+            combination.blinding += challenge * blinding.0;
+            combination.hiding |= poly.is_hiding();
+        }
+        Ok(combination)
+    }
+
+    fn open_combination(
+        ck: &IpaKey<P, T>,
+        combination: Combination<P>,
+        point: Fr<P>,
+    ) -> Result<IpaProof<P>, IpaPCError> {
+        let scheme = &ck.scheme;
+        let Combination {
+            poly,
+            commitment,
+            blinding,
+            hiding,
+        } = combination;
+        let eval = poly.evaluate(&point);
+        let proof = match hiding {
+            true => {
+                let commitment = UnsafeHidingCommitment(commitment.0, blinding);
+                IpaProof::Hiding(scheme.open(commitment, &poly.coeffs, point, eval)?)
+            }
+            false => IpaProof::Opening(scheme.open(commitment, &poly.coeffs, point, eval)?),
+        };
+        Ok(proof)
+    }
+
+    fn check_combination(
+        vk: &IpaVerifierKey<P, T>,
+        commitment: Commitment<P, false>,
+        point: Fr<P>,
+        value: Fr<P>,
+        proof: &IpaProof<P>,
+    ) -> bool {
+        let vk = &vk.vk;
+        let result = match proof.clone() {
+            IpaProof::Opening(opening) if opening.point == point => vk.verify(commitment, opening),
+            IpaProof::Hiding(opening) if opening.point == point => {
+                vk.verify(Commitment::<P, true>(commitment.0), opening)
+            }
+            _ => return false,
+        };
+        result == Ok(value)
+    }
+
+    ///groups the labels of the query set by point, in the order of the point labels
+    fn group_queries(query_set: &QuerySet<Fr<P>>) -> Vec<(Fr<P>, BTreeSet<&String>)> {
+        let mut groups = BTreeMap::new();
+        for (label, (point_label, point)) in query_set {
+            let group = groups
+                .entry(point_label)
+                .or_insert((*point, BTreeSet::new()));
+            group.1.insert(label);
+        }
+        groups.into_values().collect()
+    }
+}
+
+impl<P, T> PolynomialCommitment<Fr<P>, Poly<P>> for IpaPC<P, T>
+where
+    P: SWModelParameters + Copy,
+    T: Transcript<P>,
+{
+    type UniversalParams = IpaKey<P, T>;
+    type CommitterKey = IpaKey<P, T>;
+    type VerifierKey = IpaVerifierKey<P, T>;
+    type PreparedVerifierKey = IpaVerifierKey<P, T>;
+    type Commitment = Commitment<P, false>;
+    type PreparedCommitment = Commitment<P, false>;
+    type Randomness = Blinding<P>;
+    type Proof = IpaProof<P>;
+    type BatchProof = IpaBatchProof<P>;
+    type Error = IpaPCError;
+
+    fn setup<R: RngCore>(
+        max_degree: usize,
+        _: Option<usize>,
+        rng: &mut R,
+    ) -> Result<Self::UniversalParams, Self::Error> {
+        if max_degree == 0 {
+            return Err(Error::DegreeIsZero.into());
+        }
+        let init = Init::Seed(rng.gen());
+        let scheme = IpaScheme::init(
+            init,
+            log_size(max_degree)?,
+            false,
+            StdRng::from_seed(rng.gen()),
+        )?;
+        Ok(IpaKey::new(scheme, max_degree))
+    }
+
+    fn trim(
+        pp: &Self::UniversalParams,
+        supported_degree: usize,
+        _: usize,
+        enforced_degree_bounds: Option<&[usize]>,
+    ) -> Result<(Self::CommitterKey, Self::VerifierKey), Self::Error> {
+        if let Some(bound) = enforced_degree_bounds.and_then(|bounds| bounds.first()) {
+            return Err(Error::UnsupportedDegreeBound(*bound).into());
+        }
+        if supported_degree > pp.max_degree {
+            return Err(Error::TrimmingDegreeTooLarge.into());
+        }
+        let rng = StdRng::from_seed(pp.scheme.lock_rng().gen());
+        let scheme = pp.scheme.trim(log_size(supported_degree)?, rng)?;
+        let vk = IpaVerifierKey {
+            vk: scheme.verifier_key().clone(),
+            max_degree: pp.max_degree,
+        };
+        Ok((IpaKey::new(scheme, pp.max_degree), vk))
+    }
+
+    fn commit<'a>(
+        ck: &Self::CommitterKey,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<Fr<P>, Poly<P>>>,
+        mut rng: Option<&mut dyn RngCore>,
+    ) -> Result<
+        (
+            Vec<LabeledCommitment<Self::Commitment>>,
+            Vec<Self::Randomness>,
+        ),
+        Self::Error,
+    >
+    where
+        Poly<P>: 'a,
+    {
+        let scheme = &ck.scheme;
+        let mut commitments = vec![];
+        let mut rands = vec![];
+        for poly in polynomials {
+            no_degree_bound(poly.degree_bound())?;
+            let commitment: Commitment<P, false> =
+                scheme.commit(poly.polynomial().coeffs.clone())?;
+            let blinding = match poly.hiding_bound() {
+                Some(_) => Fr::<P>::rand(rng.as_mut().ok_or(Error::MissingRng)?),
+                None => Fr::<P>::zero(),
+            };
+            let blinded = commitment.0.into_projective() + scheme.vk.blinding_basis.mul(blinding);
+            let commitment = Commitment(blinded.into_affine());
+            commitments.push(LabeledCommitment::new(
+                poly.label().clone(),
+                commitment,
+                None,
+            ));
+            rands.push(Blinding(blinding));
+        }
+        Ok((commitments, rands))
+    }
+
+    fn open_individual_opening_challenges<'a>(
+        ck: &Self::CommitterKey,
+        labeled_polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<Fr<P>, Poly<P>>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        point: &'a Fr<P>,
+        opening_challenges: &dyn Fn(u64) -> Fr<P>,
+        rands: impl IntoIterator<Item = &'a Self::Randomness>,
+        _: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Self::Error>
+    where
+        Poly<P>: 'a,
+        Self::Randomness: 'a,
+        Self::Commitment: 'a,
+    {
+        let combination =
+            Self::combine(labeled_polynomials, commitments, rands, opening_challenges)?;
+        Self::open_combination(ck, combination, *point)
+    }
+
+    fn check_individual_opening_challenges<'a>(
+        vk: &Self::VerifierKey,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        point: &'a Fr<P>,
+        values: impl IntoIterator<Item = Fr<P>>,
+        proof: &Self::Proof,
+        opening_challenges: &dyn Fn(u64) -> Fr<P>,
+        _: Option<&mut dyn RngCore>,
+    ) -> Result<bool, Self::Error>
+    where
+        Self::Commitment: 'a,
+    {
+        let mut commitment = Commitment::default();
+        let mut value = Fr::<P>::zero();
+        for (i, (labeled, eval)) in commitments.into_iter().zip(values).enumerate() {
+            no_degree_bound(labeled.degree_bound())?;
+            let challenge = opening_challenges(i as u64);
+            commitment = commitment + *labeled.commitment() * challenge;
+            value += challenge * eval;
+        }
+        Ok(Self::check_combination(
+            vk, commitment, *point, value, proof,
+        ))
+    }
+
+    ///combines the polynomials opened at each point, then amortizes the openings
+    ///of all the points unless some polynomial is hiding
+    fn batch_open_individual_opening_challenges<'a>(
+        ck: &Self::CommitterKey,
+        labeled_polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<Fr<P>, Poly<P>>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        query_set: &QuerySet<Fr<P>>,
+        opening_challenges: &dyn Fn(u64) -> Fr<P>,
+        rands: impl IntoIterator<Item = &'a Self::Randomness>,
+        _: Option<&mut dyn RngCore>,
+    ) -> Result<Self::BatchProof, Self::Error>
+    where
+        Poly<P>: 'a,
+        Self::Randomness: 'a,
+        Self::Commitment: 'a,
+    {
+        let inputs: BTreeMap<_, _> = labeled_polynomials
+            .into_iter()
+            .zip(commitments)
+            .zip(rands)
+            .map(|((poly, commitment), rand)| (poly.label(), (poly, commitment, rand)))
+            .collect();
+        let mut combinations = vec![];
+        for (point, labels) in Self::group_queries(query_set) {
+            let mut terms = vec![];
+            for label in labels {
+                let term = inputs.get(label).ok_or(Error::MissingPolynomial {
+                    label: label.clone(),
+                })?;
+                terms.push(*term);
+            }
+            let polys = terms.iter().map(|term| term.0);
+            let commitments = terms.iter().map(|term| term.1);
+            let rands = terms.iter().map(|term| term.2);
+            let combination = Self::combine(polys, commitments, rands, opening_challenges)?;
+            combinations.push((point, combination));
+        }
+        if combinations
+            .iter()
+            .any(|(_, combination)| combination.hiding)
+        {
+            let proofs = combinations
+                .into_iter()
+                .map(|(point, combination)| Self::open_combination(ck, combination, point))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(IpaBatchProof::Individual(proofs));
+        }
+        let opens = combinations
+            .iter()
+            .map(|(point, combination)| {
+                let eval = combination.poly.evaluate(point);
+                let coeffs = &*combination.poly.coeffs;
+                (combination.commitment, coeffs, *point, eval)
+            })
+            .collect();
+        Ok(IpaBatchProof::Amortized(ck.scheme.batch_open(opens)?))
+    }
+
+    fn batch_check_individual_opening_challenges<'a, R: RngCore>(
+        vk: &Self::VerifierKey,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        query_set: &QuerySet<Fr<P>>,
+        evaluations: &Evaluations<Fr<P>, Fr<P>>,
+        proof: &Self::BatchProof,
+        opening_challenges: &dyn Fn(u64) -> Fr<P>,
+        _: &mut R,
+    ) -> Result<bool, Self::Error>
+    where
+        Self::Commitment: 'a,
+    {
+        let commitments: BTreeMap<_, _> = commitments
+            .into_iter()
+            .map(|commitment| (commitment.label(), commitment))
+            .collect();
+        let mut combinations = vec![];
+        for (point, labels) in Self::group_queries(query_set) {
+            let mut commitment = Commitment::default();
+            let mut value = Fr::<P>::zero();
+            for (i, label) in labels.into_iter().enumerate() {
+                let labeled = commitments.get(label).ok_or(Error::MissingPolynomial {
+                    label: label.clone(),
+                })?;
+                no_degree_bound(labeled.degree_bound())?;
+                let eval =
+                    evaluations
+                        .get(&(label.clone(), point))
+                        .ok_or(Error::MissingEvaluation {
+                            label: label.clone(),
+                        })?;
+                let challenge = opening_challenges(i as u64);
+                commitment = commitment + *labeled.commitment() * challenge;
+                value += challenge * eval;
+            }
+            combinations.push((point, commitment, value));
+        }
+        match proof {
+            IpaBatchProof::Individual(proofs) => {
+                if proofs.len() != combinations.len() {
+                    return Ok(false);
+                }
+                let valid = combinations.into_iter().zip(proofs).all(
+                    |((point, commitment, value), proof)| {
+                        Self::check_combination(vk, commitment, point, value, proof)
+                    },
+                );
+                Ok(valid)
+            }
+            IpaBatchProof::Amortized(multi) => {
+                if multi.openings.len() != combinations.len() {
+                    return Ok(false);
+                }
+                let claims_match = combinations.iter().zip(&multi.openings).all(
+                    |((point, _, value), (opening, _))| {
+                        opening.point == *point && opening.eval == *value
+                    },
+                );
+                let commitments = combinations
+                    .iter()
+                    .map(|(_, commitment, _)| *commitment)
+                    .collect::<Vec<_>>();
+                Ok(claims_match && vk.vk.batch_verify(&commitments, multi.clone()).is_ok())
+            }
+        }
+    }
+    ///the default implementation keys the evaluations in the proof by point
+    ///label, they are keyed by polynomial label as `open_combinations` sorts them
+    fn check_combinations_individual_opening_challenges<'a, R: RngCore>(
+        vk: &Self::VerifierKey,
+        linear_combinations: impl IntoIterator<Item = &'a LinearCombination<Fr<P>>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        eqn_query_set: &QuerySet<Fr<P>>,
+        eqn_evaluations: &Evaluations<Fr<P>, Fr<P>>,
+        proof: &BatchLCProof<Fr<P>, Poly<P>, Self>,
+        opening_challenges: &dyn Fn(u64) -> Fr<P>,
+        rng: &mut R,
+    ) -> Result<bool, Self::Error>
+    where
+        Self::Commitment: 'a,
+    {
+        let BatchLCProof { proof, evals } = proof;
+        let lcs: BTreeMap<_, _> = linear_combinations
+            .into_iter()
+            .map(|lc| (lc.label(), lc))
+            .collect();
+        let mut poly_query_set = QuerySet::new();
+        for (lc_label, (point_label, point)) in eqn_query_set {
+            for (_, term) in lcs.get(lc_label).iter().flat_map(|lc| lc.iter()) {
+                if let LCTerm::PolyLabel(label) = term {
+                    poly_query_set.insert((label.clone(), (point_label.clone(), *point)));
+                }
+            }
+        }
+        let keys: BTreeSet<_> = poly_query_set
+            .iter()
+            .map(|(label, (_, point))| (label.clone(), *point))
+            .collect();
+        let evals = evals.as_deref().unwrap_or_default();
+        if evals.len() != keys.len() {
+            return Ok(false);
+        }
+        let poly_evals: Evaluations<_, _> = keys.into_iter().zip(evals.iter().copied()).collect();
+        for (lc_label, (_, point)) in eqn_query_set {
+            let lc = match lcs.get(lc_label) {
+                Some(lc) => lc,
+                None => continue,
+            };
+            let missing = |label: &String| Error::MissingEvaluation {
+                label: label.clone(),
+            };
+            let claimed = eqn_evaluations
+                .get(&(lc_label.clone(), *point))
+                .ok_or_else(|| missing(lc_label))?;
+            let mut actual = Fr::<P>::zero();
+            for (coeff, term) in lc.iter() {
+                actual += *coeff
+                    * match term {
+                        LCTerm::One => Fr::<P>::one(),
+                        LCTerm::PolyLabel(label) => *poly_evals
+                            .get(&(label.clone(), *point))
+                            .ok_or_else(|| missing(label))?,
+                    };
+            }
+            if *claimed != actual {
+                return Ok(false);
+            }
+        }
+        Self::batch_check_individual_opening_challenges(
+            vk,
+            commitments,
+            &poly_query_set,
+            &poly_evals,
+            proof,
+            opening_challenges,
+            rng,
+        )
+    }
+}
+
+#[test]
+fn poly_commit() {
+    use ark_pallas::PallasParameters;
+    use ark_poly::UVPolynomial;
+    use ark_poly_commit::LabeledPolynomial;
+    use rand::thread_rng;
+
+    type PC = IpaPC<PallasParameters>;
+    type F = Fr<PallasParameters>;
+    let rng = &mut thread_rng();
+    let pp = PC::setup(255, None, rng).unwrap();
+    let (ck, vk) = PC::trim(&pp, 15, 0, None).unwrap();
+    assert_eq!(PCCommitterKey::supported_degree(&ck), 15);
+
+    let polys = ["a", "b", "c"]
+        .into_iter()
+        .enumerate()
+        .map(|(i, label)| {
+            let poly = Poly::<PallasParameters>::rand(10 + i, rng);
+            let hiding = (label == "c").then_some(1);
+            LabeledPolynomial::new(label.to_string(), poly, None, hiding)
+        })
+        .collect::<Vec<_>>();
+    let (commitments, rands) = PC::commit(&ck, &polys, Some(rng)).unwrap();
+    let point = F::from(7_u64);
+    let challenge = F::from(3_u64);
+    let values = polys
+        .iter()
+        .map(|poly| poly.evaluate(&point))
+        .collect::<Vec<_>>();
+
+    let proof = PC::open(
+        &ck,
+        &polys,
+        &commitments,
+        &point,
+        challenge,
+        &rands,
+        Some(rng),
+    )
+    .unwrap();
+    assert!(matches!(proof, IpaProof::Hiding(_)));
+    assert!(PC::check(
+        &vk,
+        &commitments,
+        &point,
+        values.clone(),
+        &proof,
+        challenge,
+        None
+    )
+    .unwrap());
+    let mut bad_values = values;
+    bad_values[0] += F::from(1_u64);
+    assert!(!PC::check(
+        &vk,
+        &commitments,
+        &point,
+        bad_values,
+        &proof,
+        challenge,
+        None
+    )
+    .unwrap());
+
+    let mut query_set = QuerySet::new();
+    let mut evaluations = Evaluations::new();
+    for (poly, point) in polys[..2].iter().zip([F::from(2_u64), F::from(9_u64)]) {
+        query_set.insert((poly.label().clone(), (point.to_string(), point)));
+        evaluations.insert((poly.label().clone(), point), poly.evaluate(&point));
+    }
+    query_set.insert(("a".to_string(), ("9".to_string(), F::from(9_u64))));
+    evaluations.insert(
+        ("a".to_string(), F::from(9_u64)),
+        polys[0].evaluate(&F::from(9_u64)),
+    );
+    let batch = PC::batch_open(
+        &ck,
+        &polys,
+        &commitments,
+        &query_set,
+        challenge,
+        &rands,
+        None,
+    )
+    .unwrap();
+    assert!(matches!(batch, IpaBatchProof::Amortized(_)));
+    let mut bytes = vec![];
+    batch.serialize(&mut bytes).unwrap();
+    let batch = IpaBatchProof::deserialize(&*bytes).unwrap();
+    let mut bytes = vec![];
+    vk.serialize(&mut bytes).unwrap();
+    let vk = IpaVerifierKey::deserialize(&*bytes).unwrap();
+    //a basis that isn't a power of two, or bigger than the parameters allow
+    let key_bytes = |max_degree: usize, len: usize| {
+        let mut bytes = vec![];
+        max_degree.serialize(&mut bytes).unwrap();
+        pp.scheme.vk.basis[..len].serialize(&mut bytes).unwrap();
+        pp.scheme.vk.blinding_basis.serialize(&mut bytes).unwrap();
+        bytes
+    };
+    assert!(IpaVerifierKey::<PallasParameters>::deserialize(&*key_bytes(255, 16)).is_ok());
+    assert!(IpaVerifierKey::<PallasParameters>::deserialize(&*key_bytes(255, 12)).is_err());
+    assert!(IpaKey::<PallasParameters>::deserialize(&*key_bytes(255, 12)).is_err());
+    assert!(IpaVerifierKey::<PallasParameters>::deserialize(&*key_bytes(7, 16)).is_err());
+    assert!(IpaKey::<PallasParameters>::deserialize(&*key_bytes(7, 16)).is_err());
+    assert!(PC::batch_check(
+        &vk,
+        &commitments,
+        &query_set,
+        &evaluations,
+        &batch,
+        challenge,
+        rng
+    )
+    .unwrap());
+    let (key, eval) = evaluations
+        .iter()
+        .next()
+        .map(|(k, v)| (k.clone(), *v))
+        .unwrap();
+    evaluations.insert(key, eval + F::from(1_u64));
+    assert!(!PC::batch_check(
+        &vk,
+        &commitments,
+        &query_set,
+        &evaluations,
+        &batch,
+        challenge,
+        rng
+    )
+    .unwrap());
+
+    let lc = LinearCombination::new("lc", vec![(F::from(2_u64), "a"), (F::from(5_u64), "c")]);
+    let mut query_set = QuerySet::new();
+    query_set.insert(("lc".to_string(), ("x".to_string(), point)));
+    let proof = PC::open_combinations(
+        &ck,
+        [&lc],
+        &polys,
+        &commitments,
+        &query_set,
+        challenge,
+        &rands,
+        Some(rng),
+    )
+    .unwrap();
+    let mut evaluations = Evaluations::new();
+    let lc_eval =
+        F::from(2_u64) * polys[0].evaluate(&point) + F::from(5_u64) * polys[2].evaluate(&point);
+    evaluations.insert(("lc".to_string(), point), lc_eval);
+    assert!(PC::check_combinations(
+        &vk,
+        [&lc],
+        &commitments,
+        &query_set,
+        &evaluations,
+        &proof,
+        challenge,
+        rng
+    )
+    .unwrap());
+}
@@ -0,0 +1,239 @@
+use ark_ff::{BigInteger, PrimeField};
+use std::{array::from_fn, sync::OnceLock};
+
+pub const WIDTH: usize = 3;
+pub const RATE: usize = 2;
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 56;
+const ALPHA: u64 = 5;
+
+///round constants and MDS matrix of the x^5 Poseidon permutation with width 3,
+///8 full rounds and 56 partial rounds.
+///They are generated with the Grain LFSR from the Poseidon reference
+///implementation, so they are fixed for each field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoseidonParameters<F: PrimeField> {
+    round_constants: Vec<[F; WIDTH]>,
+    mds: [[F; WIDTH]; WIDTH],
+}
+
+///fields with fixed Poseidon parameters
+pub trait PoseidonField: PrimeField {
+    fn parameters() -> &'static PoseidonParameters<Self>;
+}
+
+///base field of Pallas
+impl PoseidonField for ark_pallas::Fq {
+    fn parameters() -> &'static PoseidonParameters<Self> {
+        static PARAMETERS: OnceLock<PoseidonParameters<ark_pallas::Fq>> = OnceLock::new();
+        PARAMETERS.get_or_init(PoseidonParameters::generate)
+    }
+}
+///base field of Vesta
+impl PoseidonField for ark_pallas::Fr {
+    fn parameters() -> &'static PoseidonParameters<Self> {
+        static PARAMETERS: OnceLock<PoseidonParameters<ark_pallas::Fr>> = OnceLock::new();
+        PARAMETERS.get_or_init(PoseidonParameters::generate)
+    }
+}
+
+impl<F: PrimeField> PoseidonParameters<F> {
+    pub fn generate() -> Self {
+        let mut grain = Grain::new(F::size_in_bits(), WIDTH, FULL_ROUNDS, PARTIAL_ROUNDS);
+        let round_constants = (0..FULL_ROUNDS + PARTIAL_ROUNDS)
+            .map(|_| [(); WIDTH].map(|_| grain.field_element()))
+            .collect();
+        let mds = grain.cauchy_matrix();
+        Self {
+            round_constants,
+            mds,
+        }
+    }
+    pub fn permute(&self, state: &mut [F; WIDTH]) {
+        let half = FULL_ROUNDS / 2;
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            for (elem, constant) in state.iter_mut().zip(constants) {
+                *elem += constant;
+            }
+            match round < half || round >= half + PARTIAL_ROUNDS {
+                true => state.iter_mut().for_each(|elem| *elem = elem.pow([ALPHA])),
+                false => state[0] = state[0].pow([ALPHA]),
+            }
+            *state = self
+                .mds
+                .map(|row| row.iter().zip(state.iter()).map(|(a, b)| *a * b).sum());
+        }
+    }
+}
+
+///duplex sponge over the Poseidon permutation
+#[derive(Clone, Debug)]
+pub struct PoseidonSponge<F: PoseidonField> {
+    state: [F; WIDTH],
+    position: usize,
+    squeezing: bool,
+}
+
+impl<F: PoseidonField> PoseidonSponge<F> {
+    pub fn new() -> Self {
+        Self {
+            state: [F::zero(); WIDTH],
+            position: 0,
+            squeezing: false,
+        }
+    }
+    pub fn absorb(&mut self, elements: &[F]) {
+        for element in elements {
+            if self.squeezing {
+                self.squeezing = false;
+                self.position = 0;
+            }
+            if self.position == RATE {
+                F::parameters().permute(&mut self.state);
+                self.position = 0;
+            }
+            self.state[self.position] += element;
+            self.position += 1;
+        }
+    }
+    pub fn squeeze(&mut self) -> F {
+        if !self.squeezing || self.position == RATE {
+            F::parameters().permute(&mut self.state);
+            self.squeezing = true;
+            self.position = 0;
+        }
+        let element = self.state[self.position];
+        self.position += 1;
+        element
+    }
+}
+
+impl<F: PoseidonField> Default for PoseidonSponge<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///the Grain LFSR used by the Poseidon reference to generate parameters
+struct Grain {
+    state: [bool; 80],
+    field_size: usize,
+}
+
+impl Grain {
+    fn new(field_size: usize, width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        //prime field, x^alpha sbox, then the sizes and 30 bits set
+        let fields = [
+            (1, 2),
+            (0, 4),
+            (field_size, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10),
+            ((1 << 30) - 1, 30),
+        ];
+        let mut state = [false; 80];
+        let bits = fields
+            .into_iter()
+            .flat_map(|(value, len)| (0..len).rev().map(move |i| (value >> i) & 1 == 1));
+        for (bit, value) in state.iter_mut().zip(bits) {
+            *bit = value;
+        }
+        let mut grain = Self { state, field_size };
+        for _ in 0..160 {
+            grain.clock();
+        }
+        grain
+    }
+    fn clock(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.rotate_left(1);
+        self.state[79] = bit;
+        bit
+    }
+    ///bits are taken in pairs, the second one is kept only if the first one is set
+    fn next_bit(&mut self) -> bool {
+        loop {
+            if self.clock() {
+                return self.clock();
+            }
+            self.clock();
+        }
+    }
+    fn bits(&mut self) -> Vec<bool> {
+        (0..self.field_size).map(|_| self.next_bit()).collect()
+    }
+    ///samples by rejection, as done for the round constants
+    fn field_element<F: PrimeField>(&mut self) -> F {
+        loop {
+            if let Some(elem) = F::from_repr(F::BigInt::from_bits_be(&self.bits())) {
+                return elem;
+            }
+        }
+    }
+    ///samples reducing modulo the field, as done for the MDS matrix
+    fn reduced_field_element<F: PrimeField>(&mut self) -> F {
+        let bytes = F::BigInt::from_bits_be(&self.bits()).to_bytes_le();
+        F::from_le_bytes_mod_order(&bytes)
+    }
+    ///the Cauchy matrix 1 / (x_i + y_j) for distinct random x and y
+    fn cauchy_matrix<F: PrimeField>(&mut self) -> [[F; WIDTH]; WIDTH] {
+        loop {
+            let elems = [(); 2 * WIDTH].map(|_| self.reduced_field_element::<F>());
+            let distinct = elems
+                .iter()
+                .enumerate()
+                .all(|(i, a)| elems[i + 1..].iter().all(|b| a != b));
+            if !distinct {
+                continue;
+            }
+            let (xs, ys) = elems.split_at(WIDTH);
+            let matrix: [[Option<F>; WIDTH]; WIDTH] =
+                from_fn(|i| from_fn(|j| (xs[i] + ys[j]).inverse()));
+            if matrix.iter().flatten().all(Option::is_some) {
+                return matrix.map(|row| row.map(Option::unwrap));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn to_hex<F: PrimeField>(elem: &F) -> String {
+    elem.into_repr()
+        .to_bytes_be()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[test]
+fn test_vectors() {
+    fn check<F: PoseidonField>(mds: &str, output: [&str; WIDTH]) {
+        let parameters = F::parameters();
+        assert_eq!(
+            to_hex(&parameters.round_constants[0][0]),
+            "360d7470611e473d353f628f76d110f34e71162f31003b7057538c2596426303"
+        );
+        assert_eq!(to_hex(&parameters.mds[0][0]), mds);
+        let mut state = [F::zero(), F::one(), F::from(2_u64)];
+        parameters.permute(&mut state);
+        assert_eq!(state.map(|elem| to_hex(&elem)), output);
+    }
+    check::<ark_pallas::Fq>(
+        "0ab5e5b874a68de7b3d59fbdc8c9ead497d7a0ab23850b56323f2486d7e11b63",
+        [
+            "2a526acd0b64b45394efb364f966240ff7e69a71d0b642a0aeb1bc024aeca456",
+            "13c5d1568b4aa43076ff7dae343d5512dcd42e7fbed9dafe012a3e9628e5b82a",
+            "0a49c868c6976544256fcd597984561af7cfdfe1bda42c7b359029a1d34e9ddd",
+        ],
+    );
+    check::<ark_pallas::Fr>(
+        "1853b4977c6fa22791913f56cf21af2b5f710afc43ddc5f6eb4f1f742963421f",
+        [
+            "315a1f4cdb942f7ceddd74f22f8f2ff74d43d1973dd336c60eb08ea813bebe59",
+            "3be475f2d7642bde642adee0dd13aa48413ee0eb7bbd2198f9f126e61ea165f1",
+            "25ab8aece9537168117fdb2420d8ea605019bfd4e0423fa014d542372a7ba0d9",
+        ],
+    );
+}
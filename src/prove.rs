@@ -1,14 +1,16 @@
 use crate::{
     challenges::ChallengeGenerator,
     open::OpenTrait,
+    transcript::Transcript,
     utils::{compress, compress_basis, inner_product, scalar_inner_product, split},
-    Fr, IpaScheme,
+    Fr, IpaError, IpaScheme,
 };
 use ark_ec::{
-    short_weierstrass_jacobian::GroupAffine, AffineCurve, ModelParameters, ProjectiveCurve,
-    SWModelParameters,
+    short_weierstrass_jacobian::{GroupAffine, GroupProjective},
+    AffineCurve, ModelParameters, ProjectiveCurve, SWModelParameters,
 };
-use ark_ff::{Field, One, ToBytes, UniformRand};
+use ark_ff::{Field, One, PrimeField, UniformRand};
+use ark_serialize::CanonicalSerialize;
 use rand::Rng;
 use std::fmt::Debug;
 
@@ -25,6 +27,8 @@ pub(crate) struct HidingRoundOutput<P: SWModelParameters> {
     a: Vec<Fr<P>>,
     b: Vec<Fr<P>>,
     basis: Vec<GroupAffine<P>>,
+    ///the folded basis is `basis * scale`
+    scale: Fr<P>,
     blind: Fr<P>,
     challenges: Option<Vec<(Fr<P>, Fr<P>)>>,
 }
@@ -34,8 +38,18 @@ pub(crate) struct RoundOutput<P: SWModelParameters> {
     a: Vec<Fr<P>>,
     b: Vec<Fr<P>>,
     basis: Vec<GroupAffine<P>>,
+    ///the folded basis is `basis * scale`
+    scale: Fr<P>,
     challenges: Option<Vec<(Fr<P>, Fr<P>)>>,
 }
+type GeneralRoundOutput<P> = (
+    [GroupAffine<P>; 2],
+    [Vec<Fr<P>>; 2],
+    Vec<GroupAffine<P>>,
+    Fr<P>,
+    Option<Fr<P>>,
+    Option<Vec<(Fr<P>, Fr<P>)>>,
+);
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Commitment<T: SWModelParameters, const HIDING: bool>(pub(crate) GroupAffine<T>)
 where
@@ -66,13 +80,19 @@ where
         Commitment(self.0)
     }
 }
-#[derive(Debug)]
+///instead of revealing the final `a` and blinding factor, it proves knowledge of
+///them with a Schnorr style sigma protocol, making the opening zero-knowledge
+#[derive(Debug, Clone)]
 pub struct HidingOpening<P: SWModelParameters> {
     pub(crate) point: Fr<P>,
     pub(crate) eval: Fr<P>,
     pub(crate) rounds: Vec<(GroupAffine<P>, GroupAffine<P>)>,
-    pub(crate) a: Fr<P>,
-    pub(crate) blinding_factor: Fr<P>,
+    ///commitment to the random `d` and `s`, as d * (G + b * U) + s * H
+    pub(crate) r: GroupAffine<P>,
+    ///a * c + d
+    pub(crate) z1: Fr<P>,
+    ///blinding_factor * c + s
+    pub(crate) z2: Fr<P>,
 }
 
 impl<P: SWModelParameters> From<UnsafeHidingCommitment<P>> for Commitment<P, true> {
@@ -80,46 +100,71 @@ impl<P: SWModelParameters> From<UnsafeHidingCommitment<P>> for Commitment<P, tru
         Commitment(unsafe_commitment.0)
     }
 }
+///the compressed canonical serialization of the commitment
 impl<P, const HIDING: bool> From<Commitment<P, HIDING>> for Vec<u8>
 where
     P: SWModelParameters,
 {
     fn from(commit: Commitment<P, HIDING>) -> Self {
         let mut bytes = Vec::new();
-        commit.0.write(&mut bytes).unwrap();
+        commit.serialize(&mut bytes).unwrap();
         bytes
     }
 }
 
-impl<P, R> IpaScheme<P, R>
+impl<P, R, T> IpaScheme<P, R, T>
 where
     P: ModelParameters + SWModelParameters,
     Fr<P>: One,
     R: Rng,
+    T: Transcript<P>,
 {
-    pub fn open<O>(&self, commitment: O::Commit, a: &[Fr<P>], point: Fr<P>, eval: Fr<P>) -> O
+    pub fn open<O>(
+        &self,
+        commitment: O::Commit,
+        a: &[Fr<P>],
+        point: Fr<P>,
+        eval: Fr<P>,
+    ) -> Result<O, IpaError>
     where
-        O: OpenTrait<P, R>,
+        O: OpenTrait<P, R, T>,
     {
         O::open(self, commitment, a, point, eval)
     }
+    ///pads the coefficients to the size of the basis and checks they evaluate
+    ///to `eval` at the point of `b`, so no proof is built for a false claim
+    pub(crate) fn prepare_opening(
+        &self,
+        coeffs: &[Fr<P>],
+        b: &[Fr<P>],
+        eval: Fr<P>,
+    ) -> Result<Vec<Fr<P>>, IpaError> {
+        let coeffs = self.pad(coeffs)?;
+        match scalar_inner_product::<P>(&coeffs, b)? == eval {
+            true => Ok(coeffs),
+            false => Err(IpaError::EvaluationMismatch),
+        }
+    }
 
+    #[allow(clippy::type_complexity)]
     pub(crate) fn open_recursive(
         prev: RoundOutput<P>,
         mut rounds: Vec<(GroupAffine<P>, GroupAffine<P>)>,
         point: Fr<P>,
         eval: Fr<P>,
         u: GroupAffine<P>,
-    ) -> (Opening<P>, Option<Vec<(Fr<P>, Fr<P>)>>, GroupAffine<P>) {
+        transcript: &mut ChallengeGenerator<P, T>,
+    ) -> Result<(Opening<P>, Option<Vec<(Fr<P>, Fr<P>)>>, GroupAffine<P>), IpaError> {
         let RoundOutput {
             a,
             b,
             basis,
+            scale,
             challenges,
             ..
         } = prev;
         if a.len().is_one() {
-            (
+            Ok((
                 Opening::<P> {
                     a: a[0],
                     rounds,
@@ -127,40 +172,44 @@ where
                     eval,
                 },
                 challenges,
-                basis[0],
-            )
+                basis[0].mul(scale).into_affine(),
+            ))
         } else {
-            let prev = Self::round(&*basis, &*a, &*b, u, challenges);
+            let prev = Self::round(&basis, scale, &a, &b, u, challenges, transcript)?;
             rounds.push((prev.lj, prev.rj));
-            Self::open_recursive(prev, rounds, point, eval, u)
+            Self::open_recursive(prev, rounds, point, eval, u, transcript)
         }
     }
     pub(crate) fn round(
         basis: &[GroupAffine<P>],
+        scale: Fr<P>,
         a: &[Fr<P>],
         b: &[Fr<P>],
         u: GroupAffine<P>,
         challenges: Option<Vec<(Fr<P>, Fr<P>)>>,
-    ) -> RoundOutput<P> {
-        assert_eq!(basis.len(), a.len());
-        assert_eq!(basis.len(), b.len());
-        assert!(basis.len() > 1);
-
-        let ([lj, rj], [a, b], basis, blind, challenges) =
-            Self::general_round(basis, a, b, u, None, None, None, challenges);
+        transcript: &mut ChallengeGenerator<P, T>,
+    ) -> Result<RoundOutput<P>, IpaError> {
+        let ([lj, rj], [a, b], basis, scale, blind, challenges) = Self::general_round(
+            basis, scale, a, b, u, None, None, None, challenges, transcript,
+        )?;
         debug_assert!(blind.is_none());
-        RoundOutput {
+        Ok(RoundOutput {
             lj,
             rj,
             a,
             b,
             basis,
+            scale,
             challenges,
-        }
+        })
     }
 
+    ///the basis is folded as `g_l + g_r * challenge^2` and the inverse of the
+    ///challenge goes to the scale, so each point takes a single multiplication
+    #[allow(clippy::too_many_arguments)]
     fn general_round(
         basis: &[GroupAffine<P>],
+        scale: Fr<P>,
         a: &[Fr<P>],
         b: &[Fr<P>],
         u: GroupAffine<P>,
@@ -168,53 +217,96 @@ where
         blinding_factors: Option<[Fr<P>; 2]>,
         blind: Option<Fr<P>>,
         challenges: Option<Vec<(Fr<P>, Fr<P>)>>,
-    ) -> (
-        [GroupAffine<P>; 2],
-        [Vec<Fr<P>>; 2],
-        Vec<GroupAffine<P>>,
-        Option<Fr<P>>,
-        Option<Vec<(Fr<P>, Fr<P>)>>,
-    ) {
-        assert_eq!(basis.len(), a.len());
-        assert_eq!(basis.len(), b.len());
-        assert!(basis.len() > 1);
-        let (a_l, a_r) = split(a);
-        let (b_l, b_r) = split(b);
-        let (g_l, g_r) = split(basis);
+        transcript: &mut ChallengeGenerator<P, T>,
+    ) -> Result<GeneralRoundOutput<P>, IpaError> {
+        let (a_l, a_r) = split(a)?;
+        let (b_l, b_r) = split(b)?;
+        let (g_l, g_r) = split(basis)?;
 
-        let lj = inner_product(g_r, a_l) + u.mul(scalar_inner_product::<P>(a_l, b_r));
-        let rj = inner_product(g_l, a_r) + u.mul(scalar_inner_product::<P>(a_r, b_l));
-        let (lj, rj, factors) = match blinding_factors {
-            Some(factors) => {
-                let basis = blinding_basis.unwrap();
-                (
-                    lj + basis.mul(factors[0]),
-                    rj + basis.mul(factors[1]),
-                    Some(factors),
-                )
-            }
-            None => (lj, rj, None),
+        let scale_repr = scale.into_repr();
+        let lj =
+            inner_product(g_r, a_l)?.mul(scale_repr) + u.mul(scalar_inner_product::<P>(a_l, b_r)?);
+        let rj =
+            inner_product(g_l, a_r)?.mul(scale_repr) + u.mul(scalar_inner_product::<P>(a_r, b_l)?);
+        let (lj, rj, factors) = match (blinding_basis, blinding_factors) {
+            (Some(basis), Some(factors)) => (
+                lj + basis.mul(factors[0]),
+                rj + basis.mul(factors[1]),
+                Some(factors),
+            ),
+            _ => (lj, rj, None),
         };
 
-        let [lj, rj] = [lj, rj].map(|point| point.into_affine());
+        let points = GroupProjective::batch_normalization_into_affine(&[lj, rj]);
+        let (lj, rj) = (points[0], points[1]);
 
-        let challenge = <ChallengeGenerator<P>>::round_challenge(&lj, &rj);
+        let challenge = transcript.round_challenge(&lj, &rj);
+        let inverse = challenge.inverse().ok_or(IpaError::ZeroChallenge)?;
         let blind = factors.map(|[blind_l, blind_r]| {
-            challenge.square() * blind_l
-                + blind.unwrap()
-                + challenge.inverse().unwrap().square() * blind_r
+            challenge.square() * blind_l + blind.unwrap_or_default() + inverse.square() * blind_r
         });
-        let inverse = challenge.inverse().unwrap();
-        let a = compress::<P>(a_r, a_l, challenge, inverse);
-        let b = compress::<P>(b_l, b_r, challenge, inverse);
-        let basis = compress_basis(g_l, g_r, challenge);
+        let a = compress::<P>(a_r, a_l, challenge, inverse)?;
+        let b = compress::<P>(b_l, b_r, challenge, inverse)?;
+        let basis = compress_basis(g_l, g_r, challenge.square())?;
+        let scale = scale * inverse;
         let challenges = challenges.map(|mut challenges| {
             challenges.push((challenge, inverse));
             challenges
         });
-        ([lj, rj], [a, b], basis, blind, challenges)
+        Ok(([lj, rj], [a, b], basis, scale, blind, challenges))
     }
 
+    ///the zero-knowledge rounds over coefficients already prepared, the
+    ///challenges are recorded when given a vector for them
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub(crate) fn open_hiding_prepared(
+        basis: &[GroupAffine<P>],
+        blinding_basis: GroupAffine<P>,
+        commitment: UnsafeHidingCommitment<P>,
+        coeffs: &[Fr<P>],
+        b: &[Fr<P>],
+        point: Fr<P>,
+        eval: Fr<P>,
+        rng: &mut impl Rng,
+        challenges: Option<Vec<(Fr<P>, Fr<P>)>>,
+    ) -> Result<
+        (
+            HidingOpening<P>,
+            Option<Vec<(Fr<P>, Fr<P>)>>,
+            GroupAffine<P>,
+        ),
+        IpaError,
+    > {
+        let UnsafeHidingCommitment(commitment, blinding) = commitment;
+        let commitment = Commitment::<_, true>(commitment);
+        let mut transcript = ChallengeGenerator::new_opening(&commitment, &point, &eval);
+        let u = transcript.inner_product_basis();
+        let first = Self::hiding_round(
+            basis,
+            Fr::<P>::one(),
+            coeffs,
+            b,
+            u,
+            blinding_basis,
+            rng,
+            blinding,
+            challenges,
+            &mut transcript,
+        )?;
+        let rounds = vec![(first.lj, first.rj)];
+        Self::open_recursive_hiding(
+            first,
+            rounds,
+            point,
+            eval,
+            u,
+            blinding_basis,
+            rng,
+            &mut transcript,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     pub(crate) fn open_recursive_hiding(
         prev: HidingRoundOutput<P>,
         mut rounds: Vec<(GroupAffine<P>, GroupAffine<P>)>,
@@ -223,33 +315,70 @@ where
         u: GroupAffine<P>,
         blinding_basis: GroupAffine<P>,
         rng: &mut impl Rng,
-    ) -> HidingOpening<P> {
+        transcript: &mut ChallengeGenerator<P, T>,
+    ) -> Result<
+        (
+            HidingOpening<P>,
+            Option<Vec<(Fr<P>, Fr<P>)>>,
+            GroupAffine<P>,
+        ),
+        IpaError,
+    > {
         let HidingRoundOutput {
             a,
             b,
             basis,
+            scale,
             blind,
             challenges,
             ..
         } = prev;
         if a.len().is_one() {
-            HidingOpening::<P> {
-                a: a[0],
+            let [d, s] = [(); 2].map(|_| Fr::<P>::rand(rng));
+            let final_basis = basis[0].mul(scale);
+            let combined = (final_basis + u.mul(b[0])).into_affine();
+            let r = (combined.mul(d) + blinding_basis.mul(s)).into_affine();
+            let c = transcript.sigma_challenge(&r);
+            let opening = HidingOpening::<P> {
                 rounds,
                 point,
                 eval,
-                blinding_factor: blind,
-            }
+                r,
+                z1: a[0] * c + d,
+                z2: blind * c + s,
+            };
+            Ok((opening, challenges, final_basis.into_affine()))
         } else {
-            let prev =
-                Self::hiding_round(&*basis, &*a, &*b, u, blinding_basis, rng, blind, challenges);
+            let prev = Self::hiding_round(
+                &basis,
+                scale,
+                &a,
+                &b,
+                u,
+                blinding_basis,
+                rng,
+                blind,
+                challenges,
+                transcript,
+            )?;
             rounds.push((prev.lj, prev.rj));
-            Self::open_recursive_hiding(prev, rounds, point, eval, u, blinding_basis, rng)
+            Self::open_recursive_hiding(
+                prev,
+                rounds,
+                point,
+                eval,
+                u,
+                blinding_basis,
+                rng,
+                transcript,
+            )
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn hiding_round(
         basis: &[GroupAffine<P>],
+        scale: Fr<P>,
         a: &[Fr<P>],
         b: &[Fr<P>],
         u: GroupAffine<P>,
@@ -257,14 +386,12 @@ where
         rng: &mut impl Rng,
         blind: Fr<P>,
         challenges: Option<Vec<(Fr<P>, Fr<P>)>>,
-    ) -> HidingRoundOutput<P> {
-        assert_eq!(basis.len(), a.len());
-        assert_eq!(basis.len(), b.len());
-        assert!(basis.len() > 1);
-
+        transcript: &mut ChallengeGenerator<P, T>,
+    ) -> Result<HidingRoundOutput<P>, IpaError> {
         let blinding_factors = [(); 2].map(|_| Fr::<P>::rand(rng));
-        let ([lj, rj], [a, b], basis, blind, challenges) = Self::general_round(
+        let ([lj, rj], [a, b], basis, scale, blind, challenges) = Self::general_round(
             basis,
+            scale,
             a,
             b,
             u,
@@ -272,16 +399,18 @@ where
             Some(blinding_factors),
             Some(blind),
             challenges,
-        );
-        let blind = blind.unwrap();
-        HidingRoundOutput {
+            transcript,
+        )?;
+        let blind = blind.unwrap_or_default();
+        Ok(HidingRoundOutput {
             lj,
             rj,
             a,
             b,
             basis,
+            scale,
             blind,
             challenges,
-        }
+        })
     }
 }
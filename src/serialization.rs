@@ -0,0 +1,376 @@
+use crate::{
+    accumulation::{AccumulationProof, Accumulator, DeferredOpening},
+    amortization::{MultiOpening, PointOpening},
+    multiopen::MultiPointOpening,
+    srs::cofactor_is_one,
+    transcript::Transcript,
+    Commitment, HidingOpening, Init, Opening, VerifierKey,
+};
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, SWModelParameters};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::iter::once;
+
+///points contained in a proof or a key, checked when deserializing without
+///compression because uncompressed points are only checked to be in the
+///subgroup
+trait ProofPoints<P: SWModelParameters> {
+    fn points(&self) -> Vec<GroupAffine<P>>;
+
+    fn check(&self) -> Result<(), SerializationError> {
+        let check_subgroup = !cofactor_is_one::<P>();
+        let valid = self.points().iter().all(|point| {
+            point.is_on_curve()
+                && (!check_subgroup || point.is_in_correct_subgroup_assuming_on_curve())
+        });
+        match valid {
+            true => Ok(()),
+            false => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+impl<P: SWModelParameters> ProofPoints<P> for Opening<P> {
+    fn points(&self) -> Vec<GroupAffine<P>> {
+        self.rounds.iter().flat_map(|(l, r)| [*l, *r]).collect()
+    }
+}
+
+impl<P: SWModelParameters> ProofPoints<P> for HidingOpening<P> {
+    fn points(&self) -> Vec<GroupAffine<P>> {
+        let rounds = self.rounds.iter().flat_map(|(l, r)| [*l, *r]);
+        rounds.chain(once(self.r)).collect()
+    }
+}
+
+impl<P: SWModelParameters, const HIDING: bool> ProofPoints<P> for Commitment<P, HIDING> {
+    fn points(&self) -> Vec<GroupAffine<P>> {
+        vec![self.0]
+    }
+}
+
+impl<P: SWModelParameters, O: ProofPoints<P>> ProofPoints<P> for MultiOpening<P, O> {
+    fn points(&self) -> Vec<GroupAffine<P>> {
+        let openings = self
+            .openings
+            .iter()
+            .flat_map(|(opening, basis)| opening.points().into_iter().chain(once(*basis)));
+        openings.chain(self.batch_opening.points()).collect()
+    }
+}
+
+impl<P: SWModelParameters> ProofPoints<P> for PointOpening<P> {
+    fn points(&self) -> Vec<GroupAffine<P>> {
+        self.opening.points()
+    }
+}
+
+impl<P: SWModelParameters> ProofPoints<P> for MultiPointOpening<P> {
+    fn points(&self) -> Vec<GroupAffine<P>> {
+        let mut points = self.opening.points();
+        points.push(self.quotient);
+        points
+    }
+}
+
+impl<P: SWModelParameters> ProofPoints<P> for DeferredOpening<P> {
+    fn points(&self) -> Vec<GroupAffine<P>> {
+        let mut points = self.opening.points();
+        points.push(self.final_basis);
+        points
+    }
+}
+
+impl<P: SWModelParameters> ProofPoints<P> for Accumulator<P> {
+    fn points(&self) -> Vec<GroupAffine<P>> {
+        vec![self.final_basis]
+    }
+}
+
+impl<P: SWModelParameters> ProofPoints<P> for AccumulationProof<P> {
+    fn points(&self) -> Vec<GroupAffine<P>> {
+        let mut points = self.final_bases.clone();
+        points.extend(self.opening.points());
+        points
+    }
+}
+
+impl<P: SWModelParameters, T: Transcript<P>> ProofPoints<P> for VerifierKey<P, T> {
+    fn points(&self) -> Vec<GroupAffine<P>> {
+        let mut points = self.basis.clone();
+        points.push(self.blinding_basis);
+        points
+    }
+}
+
+///serializes the fields in order, compressed deserialization relies on the
+///checks of the points themselves
+macro_rules! impl_canonical {
+    ($name:ident, $($field:ident),+) => {
+        impl_canonical!($name<P>, $($field),+);
+    };
+    ($ty:ty, $($field:ident),+) => {
+        impl<P: SWModelParameters> CanonicalSerialize for $ty {
+            fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+                $(self.$field.serialize(&mut writer)?;)+
+                Ok(())
+            }
+            fn serialized_size(&self) -> usize {
+                0 $(+ self.$field.serialized_size())+
+            }
+            fn serialize_uncompressed<W: Write>(
+                &self,
+                mut writer: W,
+            ) -> Result<(), SerializationError> {
+                $(self.$field.serialize_uncompressed(&mut writer)?;)+
+                Ok(())
+            }
+            fn serialize_unchecked<W: Write>(
+                &self,
+                mut writer: W,
+            ) -> Result<(), SerializationError> {
+                $(self.$field.serialize_unchecked(&mut writer)?;)+
+                Ok(())
+            }
+            fn uncompressed_size(&self) -> usize {
+                0 $(+ self.$field.uncompressed_size())+
+            }
+        }
+        impl<P: SWModelParameters> CanonicalDeserialize for $ty {
+            fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+                Ok(Self {
+                    $($field: CanonicalDeserialize::deserialize(&mut reader)?,)+
+                })
+            }
+            fn deserialize_uncompressed<R: Read>(reader: R) -> Result<Self, SerializationError> {
+                let proof = Self::deserialize_unchecked(reader)?;
+                proof.check()?;
+                Ok(proof)
+            }
+            fn deserialize_unchecked<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+                Ok(Self {
+                    $($field: CanonicalDeserialize::deserialize_unchecked(&mut reader)?,)+
+                })
+            }
+        }
+    };
+}
+
+impl<P: SWModelParameters, const HIDING: bool> CanonicalSerialize for Commitment<P, HIDING> {
+    fn serialize<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        self.0.serialize(writer)
+    }
+    fn serialized_size(&self) -> usize {
+        self.0.serialized_size()
+    }
+    fn serialize_uncompressed<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        self.0.serialize_uncompressed(writer)
+    }
+    fn serialize_unchecked<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        self.0.serialize_unchecked(writer)
+    }
+    fn uncompressed_size(&self) -> usize {
+        self.0.uncompressed_size()
+    }
+}
+impl<P: SWModelParameters, const HIDING: bool> CanonicalDeserialize for Commitment<P, HIDING> {
+    fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        Ok(Self(CanonicalDeserialize::deserialize(reader)?))
+    }
+    fn deserialize_uncompressed<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        let commitment = Self::deserialize_unchecked(reader)?;
+        commitment.check()?;
+        Ok(commitment)
+    }
+    fn deserialize_unchecked<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        Ok(Self(CanonicalDeserialize::deserialize_unchecked(reader)?))
+    }
+}
+
+///the basis and then the blinding basis, the size of the basis must follow the
+///rules of `VerifierKey::init`
+impl<P: SWModelParameters, T: Transcript<P>> CanonicalSerialize for VerifierKey<P, T> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.basis.serialize(&mut writer)?;
+        self.blinding_basis.serialize(&mut writer)
+    }
+    fn serialized_size(&self) -> usize {
+        self.basis.serialized_size() + self.blinding_basis.serialized_size()
+    }
+    fn serialize_uncompressed<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.basis.serialize_uncompressed(&mut writer)?;
+        self.blinding_basis.serialize_uncompressed(&mut writer)
+    }
+    fn serialize_unchecked<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.basis.serialize_unchecked(&mut writer)?;
+        self.blinding_basis.serialize_unchecked(&mut writer)
+    }
+    fn uncompressed_size(&self) -> usize {
+        self.basis.uncompressed_size() + self.blinding_basis.uncompressed_size()
+    }
+}
+impl<P: SWModelParameters, T: Transcript<P>> VerifierKey<P, T> {
+    fn from_elements(
+        basis: Vec<GroupAffine<P>>,
+        blinding_basis: GroupAffine<P>,
+    ) -> Result<Self, SerializationError> {
+        if !basis.len().is_power_of_two() {
+            return Err(SerializationError::InvalidData);
+        }
+        let log_size = basis.len().trailing_zeros() as u8;
+        Self::init(Init::Elements(basis, blinding_basis), log_size)
+            .map_err(|_| SerializationError::InvalidData)
+    }
+}
+impl<P: SWModelParameters, T: Transcript<P>> CanonicalDeserialize for VerifierKey<P, T> {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let basis = CanonicalDeserialize::deserialize(&mut reader)?;
+        let blinding_basis = CanonicalDeserialize::deserialize(&mut reader)?;
+        Self::from_elements(basis, blinding_basis)
+    }
+    fn deserialize_uncompressed<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        let vk = Self::deserialize_unchecked(reader)?;
+        vk.check()?;
+        Ok(vk)
+    }
+    fn deserialize_unchecked<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let basis = CanonicalDeserialize::deserialize_unchecked(&mut reader)?;
+        let blinding_basis = CanonicalDeserialize::deserialize_unchecked(&mut reader)?;
+        Self::from_elements(basis, blinding_basis)
+    }
+}
+
+///string encodings of the compressed serialization, decoding checks the point
+///and rejects trailing bytes
+impl<P: SWModelParameters, const HIDING: bool> Commitment<P, HIDING> {
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+    pub fn from_hex(string: &str) -> Result<Self, SerializationError> {
+        let bytes = hex::decode(string).map_err(|_| SerializationError::InvalidData)?;
+        Self::from_bytes(&bytes)
+    }
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.to_bytes())
+    }
+    pub fn from_base64(string: &str) -> Result<Self, SerializationError> {
+        let bytes = STANDARD
+            .decode(string)
+            .map_err(|_| SerializationError::InvalidData)?;
+        Self::from_bytes(&bytes)
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.serialize(&mut bytes).unwrap();
+        bytes
+    }
+    fn from_bytes(mut bytes: &[u8]) -> Result<Self, SerializationError> {
+        let commitment = Self::deserialize(&mut bytes)?;
+        match bytes.is_empty() {
+            true => Ok(commitment),
+            false => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+impl_canonical!(Opening, point, eval, rounds, a);
+impl_canonical!(HidingOpening, point, eval, rounds, r, z1, z2);
+impl_canonical!(MultiOpening, openings, batch_opening);
+impl_canonical!(MultiOpening<P, HidingOpening<P>>, openings, batch_opening);
+impl_canonical!(PointOpening, evals, opening);
+impl_canonical!(MultiPointOpening, evals, quotient, opening);
+impl_canonical!(DeferredOpening, opening, final_basis);
+impl_canonical!(Accumulator, challenges, final_basis);
+impl_canonical!(AccumulationProof, final_bases, opening);
+
+#[test]
+fn serialization() {
+    use crate::{tests::commit_and_open, Init, IpaScheme, UnsafeHidingCommitment};
+    use ark_ff::One;
+    use ark_pallas::{Fq, PallasParameters};
+    use rand::thread_rng;
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, false, thread_rng()).unwrap();
+    let (commit, poly, point, eval) = commit_and_open(&scheme);
+    let opening: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    for compress in [true, false] {
+        let mut bytes = vec![];
+        let proof = match compress {
+            true => {
+                opening.serialize(&mut bytes).unwrap();
+                assert_eq!(bytes.len(), opening.serialized_size());
+                Opening::deserialize(&*bytes).unwrap()
+            }
+            false => {
+                opening.serialize_uncompressed(&mut bytes).unwrap();
+                assert_eq!(bytes.len(), opening.uncompressed_size());
+                Opening::deserialize_uncompressed(&*bytes).unwrap()
+            }
+        };
+        assert_eq!(scheme.verify(commit, proof).unwrap(), eval);
+    }
+
+    let hiding: UnsafeHidingCommitment<_> = scheme.commit(poly.clone()).unwrap();
+    let opening: HidingOpening<_> = scheme.open(hiding, &poly, point, eval).unwrap();
+    let mut bytes = vec![];
+    opening.serialize(&mut bytes).unwrap();
+    let proof = HidingOpening::deserialize(&*bytes).unwrap();
+    assert_eq!(scheme.verify(hiding.into(), proof).unwrap(), eval);
+
+    let multi = scheme
+        .batch_open(vec![(commit, &*poly, point, eval)])
+        .unwrap();
+    let mut bytes = vec![];
+    multi.serialize_uncompressed(&mut bytes).unwrap();
+    let multi = MultiOpening::<_>::deserialize_uncompressed(&*bytes).unwrap();
+    assert!(scheme.batch_verify(&[commit], multi).is_ok());
+
+    let multi = scheme
+        .batch_open_hiding(vec![(hiding, &*poly, point, eval)])
+        .unwrap();
+    let mut bytes = vec![];
+    multi.serialize_uncompressed(&mut bytes).unwrap();
+    let multi = MultiOpening::<_, HidingOpening<_>>::deserialize_uncompressed(&*bytes).unwrap();
+    assert!(scheme.batch_verify(&[hiding.clean()], multi).is_ok());
+
+    let point_opening = scheme
+        .batch_open_point(vec![(commit, &*poly, eval)], point)
+        .unwrap();
+    let mut bytes = vec![];
+    point_opening.serialize(&mut bytes).unwrap();
+    let point_opening = PointOpening::deserialize(&*bytes).unwrap();
+    assert_eq!(
+        scheme.batch_verify_point(&[commit], point_opening).unwrap(),
+        vec![eval]
+    );
+
+    let bytes: Vec<u8> = commit.into();
+    assert_eq!(Commitment::deserialize(&*bytes).unwrap(), commit);
+    let mut bytes = vec![];
+    commit.serialize_uncompressed(&mut bytes).unwrap();
+    assert_eq!(
+        Commitment::deserialize_uncompressed(&*bytes).unwrap(),
+        commit
+    );
+    assert_eq!(Commitment::from_hex(&commit.to_hex()).unwrap(), commit);
+    assert_eq!(
+        Commitment::from_base64(&commit.to_base64()).unwrap(),
+        commit
+    );
+    assert!(Commitment::<PallasParameters, false>::from_hex(&(commit.to_hex() + "00")).is_err());
+    assert!(Commitment::<PallasParameters, false>::from_base64("not base64").is_err());
+
+    let mut moved = commit;
+    moved.0.y += Fq::one();
+    let mut bytes = vec![];
+    moved.serialize_uncompressed(&mut bytes).unwrap();
+    assert!(Commitment::<PallasParameters, false>::deserialize_uncompressed(&*bytes).is_err());
+
+    //a point moved out of the curve must be rejected
+    let mut opening = opening;
+    opening.r.y += Fq::one();
+    let mut bytes = vec![];
+    opening.serialize_uncompressed(&mut bytes).unwrap();
+    assert!(HidingOpening::<PallasParameters>::deserialize_uncompressed(&*bytes).is_err());
+}
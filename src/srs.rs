@@ -0,0 +1,185 @@
+use crate::{transcript::Transcript, Fr, IpaScheme, VerifierKey};
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, SWModelParameters};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::Rng;
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    marker::PhantomData,
+    sync::Mutex,
+};
+
+const MAGIC: &[u8; 8] = b"IPAPCSRS";
+const VERSION: u32 = 1;
+const DIGEST_SIZE: usize = 32;
+
+fn invalid(reason: impl ToString) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, reason.to_string())
+}
+
+///identifies the curve by hashing its coefficients and generator, so a file
+///can't be loaded for a different curve
+fn curve_id<P: SWModelParameters>() -> [u8; 32] {
+    let mut bytes = vec![];
+    P::COEFF_A.serialize_unchecked(&mut bytes).unwrap();
+    P::COEFF_B.serialize_unchecked(&mut bytes).unwrap();
+    let generator = GroupAffine::<P>::new(
+        P::AFFINE_GENERATOR_COEFFS.0,
+        P::AFFINE_GENERATOR_COEFFS.1,
+        false,
+    );
+    generator.serialize_unchecked(&mut bytes).unwrap();
+    blake3::hash(&bytes).into()
+}
+
+pub(crate) fn cofactor_is_one<P: SWModelParameters>() -> bool {
+    match P::COFACTOR.split_first() {
+        Some((low, high)) => *low == 1 && high.iter().all(|limb| *limb == 0),
+        None => false,
+    }
+}
+
+impl<P, R, T> IpaScheme<P, R, T>
+where
+    P: SWModelParameters,
+    R: Rng,
+    T: Transcript<P>,
+{
+    ///writes the reference string, including the evaluation basis if any, so it
+    ///can be loaded without recomputing it.
+    ///The file has the magic bytes, the version, the curve identifier, the size,
+    ///the points uncompressed and a blake3 digest of everything before it
+    pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&curve_id::<P>());
+        bytes.extend_from_slice(&(self.vk.max_degree as u64).to_le_bytes());
+        bytes.push(self.evaluation_basis.is_some() as u8);
+        let evaluation_basis = self.evaluation_basis.iter().flatten();
+        let points = [&self.vk.blinding_basis].into_iter().chain(&self.vk.basis);
+        for point in points.chain(evaluation_basis) {
+            point.serialize_uncompressed(&mut bytes).map_err(invalid)?;
+        }
+        let digest = blake3::hash(&bytes);
+        writer.write_all(&bytes)?;
+        writer.write_all(digest.as_bytes())?;
+        Ok(())
+    }
+
+    ///reads a reference string written by `save`, failing if the digest, the
+    ///version or the curve don't match.
+    ///The size follows the rules of `VerifierKey::init` and the points are
+    ///checked to be on the curve and, unless the cofactor is one, in the
+    ///subgroup. The digest only protects against corruption so the file must
+    ///come from a trusted source
+    pub fn load<Rd: Read>(mut reader: Rd, rng: R) -> io::Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        let header_size = MAGIC.len() + 4 + 32 + 8 + 1;
+        if bytes.len() < header_size + DIGEST_SIZE {
+            return Err(invalid("srs file too short"));
+        }
+        let (content, digest) = bytes.split_at(bytes.len() - DIGEST_SIZE);
+        if blake3::hash(content).as_bytes() != digest {
+            return Err(invalid("srs digest mismatch"));
+        }
+        let (header, mut content) = content.split_at(header_size);
+        let (magic, header) = header.split_at(MAGIC.len());
+        let (version, header) = header.split_at(4);
+        let (id, header) = header.split_at(32);
+        let (size, commit_to_evals) = header.split_at(8);
+        if magic != MAGIC {
+            return Err(invalid("not an srs file"));
+        }
+        if version != VERSION.to_le_bytes() {
+            return Err(invalid("unsupported srs version"));
+        }
+        if id != curve_id::<P>() {
+            return Err(invalid("srs for a different curve"));
+        }
+        let max_degree = u64::from_le_bytes(size.try_into().unwrap()) as usize;
+        if !max_degree.is_power_of_two() {
+            return Err(invalid("srs size is not a power of two"));
+        }
+        if max_degree <= 1 || Radix2EvaluationDomain::<Fr<P>>::new(max_degree).is_none() {
+            return Err(invalid("unsupported srs size"));
+        }
+        //every point of the curve is in the subgroup when the cofactor is one,
+        //as for Pallas and Vesta
+        let check_subgroup = !cofactor_is_one::<P>();
+        let mut point = || {
+            let point = GroupAffine::<P>::deserialize_unchecked(&mut content).map_err(invalid)?;
+            if !point.is_on_curve() {
+                return Err(invalid("srs point not on the curve"));
+            }
+            match !check_subgroup || point.is_in_correct_subgroup_assuming_on_curve() {
+                true => Ok(point),
+                false => Err(invalid("srs point not in the subgroup")),
+            }
+        };
+        let blinding_basis = point()?;
+        let basis = (0..max_degree).map(|_| point()).collect::<Result<_, _>>()?;
+        let evaluation_basis = match commit_to_evals {
+            [0] => None,
+            [1] => Some((0..max_degree).map(|_| point()).collect::<Result<_, _>>()?),
+            _ => return Err(invalid("invalid srs flag")),
+        };
+        if !content.is_empty() {
+            return Err(invalid("trailing bytes in srs file"));
+        }
+        let vk = VerifierKey {
+            basis,
+            blinding_basis,
+            max_degree,
+            _transcript: PhantomData,
+        };
+        Ok(Self {
+            vk,
+            evaluation_basis,
+            rng: Mutex::new(rng),
+        })
+    }
+}
+
+#[test]
+fn save_and_load() {
+    use crate::{Commitment, Init};
+    use ark_pallas::{Fr, PallasParameters};
+    use ark_vesta::VestaParameters;
+    use rand::thread_rng;
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 4, true, thread_rng()).unwrap();
+    let mut file = vec![];
+    scheme.save(&mut file).unwrap();
+    let loaded = IpaScheme::<PallasParameters, _>::load(&*file, thread_rng()).unwrap();
+    assert_eq!(loaded.vk.basis, scheme.vk.basis);
+    assert_eq!(loaded.evaluation_basis, scheme.evaluation_basis);
+    assert_eq!(loaded.vk.blinding_basis, scheme.vk.blinding_basis);
+    let poly = (0..16).map(Fr::from).collect::<Vec<_>>();
+    let commit: Commitment<_, false> = scheme.commit(poly.clone()).unwrap();
+    let loaded_commit: Commitment<_, false> = loaded.commit(poly).unwrap();
+    assert_eq!(commit, loaded_commit);
+
+    let mut corrupted = file.clone();
+    corrupted[60] ^= 1;
+    assert!(IpaScheme::<PallasParameters, _>::load(&*corrupted, thread_rng()).is_err());
+    assert!(IpaScheme::<VestaParameters, _>::load(&*file, thread_rng()).is_err());
+    assert!(IpaScheme::<PallasParameters, _>::load(&file[..20], thread_rng()).is_err());
+
+    //a well formed file of size 1, which `init` doesn't allow
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 1, false, thread_rng()).unwrap();
+    let mut file = vec![];
+    scheme.save(&mut file).unwrap();
+    let point_size = (file.len() - 53 - DIGEST_SIZE) / 3;
+    let mut small = file[..file.len() - DIGEST_SIZE - point_size].to_vec();
+    small[44..52].copy_from_slice(&1_u64.to_le_bytes());
+    let digest = blake3::hash(&small);
+    small.extend_from_slice(digest.as_bytes());
+    let err = IpaScheme::<PallasParameters, _>::load(&*small, thread_rng())
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "unsupported srs size");
+    assert!(cofactor_is_one::<PallasParameters>() && cofactor_is_one::<VestaParameters>());
+}
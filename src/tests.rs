@@ -1,5 +1,10 @@
-use crate::{Commitment, Fr, HidingOpening, Init, IpaScheme, Opening, UnsafeHidingCommitment};
-use ark_ec::SWModelParameters;
+use crate::{
+    poseidon::PoseidonField,
+    transcript::{Blake3Transcript, PoseidonTranscript, Transcript},
+    Commitment, Fr, HidingOpening, Init, IpaError, IpaScheme, Opening, UnsafeHidingCommitment,
+    VerifierKey,
+};
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, AffineCurve, SWModelParameters};
 use ark_ff::One;
 use ark_pallas::{Fr as F, PallasParameters};
 use ark_poly::{Polynomial, UVPolynomial};
@@ -7,56 +12,443 @@ use rand::{thread_rng, Rng};
 
 #[test]
 fn test_hiding() {
-    let scheme = IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 3, true, thread_rng());
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 3, true, thread_rng()).unwrap();
     let poly = [1, 2, 3, 4, 5, 6, 7, 8].map(F::from).to_vec();
     //let commit = scheme.commit_hiding(poly.clone(), &mut rng);
-    let commit: UnsafeHidingCommitment<_> = scheme.commit(poly.clone());
+    let commit: UnsafeHidingCommitment<_> = scheme.commit(poly.clone()).unwrap();
     let point = F::from(5);
     let eval = {
-        let poly = ark_poly::univariate::DensePolynomial::<F>::from_coefficients_slice(&*poly);
+        let poly = ark_poly::univariate::DensePolynomial::<F>::from_coefficients_slice(&poly);
         poly.evaluate(&point)
     };
     //let proof = scheme.open_hiding(commit.into(), &poly, point, eval, &mut rng);
-    let proof: HidingOpening<_> = scheme.open(commit, &poly, point, eval);
-    let bad_proof: HidingOpening<_> = scheme.open(commit.into(), &poly, point, eval + F::one());
+    let proof: HidingOpening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    let bad_open = scheme.open::<HidingOpening<_>>(commit, &poly, point, eval + F::one());
+    assert_eq!(bad_open.err(), Some(IpaError::EvaluationMismatch));
+    let mut bad_proof: HidingOpening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    bad_proof.eval += F::one();
     assert_eq!(scheme.verify(commit.into(), proof).unwrap(), eval);
-    assert!(scheme.verify(commit.into(), bad_proof).is_none());
+    assert!(scheme.verify(commit.into(), bad_proof).is_err());
+}
+
+#[test]
+fn test_zk_response() {
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 3, true, thread_rng()).unwrap();
+    let poly = [1, 2, 3, 4, 5, 6, 7, 8].map(F::from).to_vec();
+    let commit: UnsafeHidingCommitment<_> = scheme.commit(poly.clone()).unwrap();
+    let point = F::from(5);
+    let eval = {
+        let poly = ark_poly::univariate::DensePolynomial::<F>::from_coefficients_slice(&poly);
+        poly.evaluate(&point)
+    };
+    let mut proof: HidingOpening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    proof.z1 += F::one();
+    assert!(scheme.verify(commit.into(), proof).is_err());
+    let mut proof: HidingOpening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    proof.z2 += F::one();
+    assert!(scheme.verify(commit.into(), proof).is_err());
 }
 
 #[test]
 fn test_binding() {
-    let scheme = IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 3, true, thread_rng());
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 3, true, thread_rng()).unwrap();
     let poly = [1, 2, 3, 4, 5, 6, 7, 8].map(F::from).to_vec();
-    let commit = scheme.commit(poly.clone());
+    let commit = scheme.commit(poly.clone()).unwrap();
     let point = F::from(5);
     let eval = {
-        let poly = ark_poly::univariate::DensePolynomial::<F>::from_coefficients_slice(&*poly);
+        let poly = ark_poly::univariate::DensePolynomial::<F>::from_coefficients_slice(&poly);
         poly.evaluate(&point)
     };
-    let proof: Opening<_> = scheme.open(commit, &poly, point, eval);
-    let bad_proof: Opening<_> = scheme.open(commit, &poly, point, eval + F::one());
+    let proof: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    let bad_open = scheme.open::<Opening<_>>(commit, &poly, point, eval + F::one());
+    assert_eq!(bad_open.err(), Some(IpaError::EvaluationMismatch));
+    let mut bad_proof = proof.clone();
+    bad_proof.eval += F::one();
     assert_eq!(scheme.verify(commit, proof).unwrap(), eval);
-    assert!(scheme.verify(commit, bad_proof).is_none());
+    assert_eq!(
+        scheme.verify(commit, bad_proof).unwrap_err(),
+        IpaError::FinalCheckFailed
+    );
+}
+
+#[test]
+fn test_transcripts() {
+    fn open_with<T: Transcript<PallasParameters>>() -> (
+        Commitment<PallasParameters, false>,
+        Opening<PallasParameters>,
+    ) {
+        let scheme =
+            IpaScheme::<PallasParameters, _, T>::init(Init::Seed(1), 3, false, thread_rng())
+                .unwrap();
+        let poly = [1, 2, 3, 4, 5, 6, 7, 8].map(F::from).to_vec();
+        let commit = scheme.commit(poly.clone()).unwrap();
+        let point = F::from(5);
+        let eval = {
+            let poly = ark_poly::univariate::DensePolynomial::<F>::from_coefficients_slice(&poly);
+            poly.evaluate(&point)
+        };
+        let proof: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+        assert_eq!(scheme.verify(commit, proof.clone()).unwrap(), eval);
+        (commit, proof)
+    }
+    #[cfg(feature = "sha2")]
+    open_with::<crate::transcript::Sha256Transcript>();
+    #[cfg(feature = "sha3")]
+    open_with::<crate::transcript::KeccakTranscript>();
+    #[cfg(feature = "merlin")]
+    open_with::<crate::transcript::MerlinTranscript>();
+    let (commit, proof) = open_with::<Blake3Transcript>();
+    let scheme = IpaScheme::<PallasParameters, _, PoseidonTranscript<ark_pallas::Fq>>::init(
+        Init::Seed(1),
+        3,
+        false,
+        thread_rng(),
+    )
+    .unwrap();
+    assert!(scheme.verify(commit, proof).is_err());
 }
 
-pub(crate) fn commit_and_open<P, R>(
-    scheme: &IpaScheme<P, R>,
+#[test]
+fn test_poseidon_transcript() {
+    fn check<P>()
+    where
+        P: SWModelParameters,
+        P::BaseField: PoseidonField,
+        Fr<P>: From<i32>,
+        Commitment<P, false>: Copy,
+    {
+        let scheme = IpaScheme::<P, _, PoseidonTranscript<P::BaseField>>::init(
+            Init::Seed(1),
+            8,
+            false,
+            thread_rng(),
+        )
+        .unwrap();
+        let (commit, poly, point, eval) = commit_and_open(&scheme);
+        let proof: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+        assert_eq!(scheme.verify(commit, proof).unwrap(), eval);
+    }
+    check::<PallasParameters>();
+    check::<ark_vesta::VestaParameters>();
+
+    type Poseidon = PoseidonTranscript<ark_pallas::Fq>;
+    let mut transcript = <Poseidon as Transcript<PallasParameters>>::new(b"test");
+    Transcript::<PallasParameters>::absorb_point(
+        &mut transcript,
+        &GroupAffine::prime_subgroup_generator(),
+    );
+    Transcript::<PallasParameters>::absorb_scalar(&mut transcript, &F::from(7));
+    let challenge = Transcript::<PallasParameters>::squeeze_challenge(&mut transcript);
+    assert_eq!(challenge, F::from(0x1f7e28489c8b7db6c09fc95ce8f0c4c4_u128));
+}
+
+pub(crate) fn commit_and_open<P, R, T>(
+    scheme: &IpaScheme<P, R, T>,
 ) -> (Commitment<P, false>, Vec<Fr<P>>, Fr<P>, Fr<P>)
 where
     P: SWModelParameters,
     Fr<P>: From<i32>,
     R: Rng,
+    T: Transcript<P>,
 {
     use ark_ff::UniformRand;
 
     type Poly<P> = ark_poly::univariate::DensePolynomial<Fr<P>>;
     let mut rng = thread_rng();
     let poly = [0; 256].map(|_| Fr::<P>::rand(&mut rng)).to_vec();
-    let commit = scheme.commit(poly.clone());
+    let commit = scheme.commit(poly.clone()).unwrap();
     let point = Fr::<P>::from(5);
     let eval = {
-        let poly = Poly::<P>::from_coefficients_slice(&*poly);
+        let poly = Poly::<P>::from_coefficients_slice(&poly);
         poly.evaluate(&point)
     };
     (commit, poly, point, eval)
 }
+
+#[test]
+fn test_wide_reduction() {
+    use ark_ff::PrimeField;
+
+    let mut transcript = <Blake3Transcript as Transcript<PallasParameters>>::new(b"test");
+    Transcript::<PallasParameters>::absorb_scalar(&mut transcript, &F::from(7));
+    let challenge = Transcript::<PallasParameters>::squeeze_challenge(&mut transcript);
+
+    let mut data = b"test".to_vec();
+    data.push(7);
+    data.extend([0; 31]);
+    let first = blake3::hash(&data);
+    data.extend(first.as_bytes());
+    let second = blake3::hash(&data);
+    let wide = [*first.as_bytes(), *second.as_bytes()].concat();
+    assert_eq!(challenge, F::from_le_bytes_mod_order(&wide));
+}
+
+#[test]
+fn test_hash_to_curve_init() {
+    let scheme = IpaScheme::<PallasParameters, _>::init(
+        Init::HashToCurve("ipapc-test".into()),
+        3,
+        true,
+        thread_rng(),
+    )
+    .unwrap();
+    let elements = Init::<PallasParameters>::HashToCurve("ipapc-test".into())
+        .to_elements(8)
+        .unwrap();
+    assert_eq!(scheme.vk.basis, elements.0);
+    assert!(!scheme.vk.basis.contains(&scheme.vk.blinding_basis));
+
+    let poly = [1, 2, 3, 4, 5, 6, 7, 8].map(F::from).to_vec();
+    let commit: UnsafeHidingCommitment<_> = scheme.commit(poly.clone()).unwrap();
+    let point = F::from(5);
+    let eval = {
+        let poly = ark_poly::univariate::DensePolynomial::<F>::from_coefficients_slice(&poly);
+        poly.evaluate(&point)
+    };
+    let proof: HidingOpening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    assert_eq!(scheme.verify(commit.into(), proof).unwrap(), eval);
+}
+
+#[test]
+fn test_errors() {
+    type Scheme = IpaScheme<PallasParameters, rand::rngs::ThreadRng>;
+    let init = |init, size| Scheme::init(init, size, false, thread_rng()).err();
+    assert_eq!(init(Init::Seed(1), 0), Some(IpaError::InvalidSize(0)));
+    assert_eq!(init(Init::Seed(1), 200), Some(IpaError::InvalidSize(200)));
+    let long_domain = "a".repeat(300);
+    assert_eq!(
+        init(Init::HashToCurve(long_domain), 2),
+        Some(IpaError::DomainTooLong)
+    );
+    let elements = Init::Elements(vec![GroupAffine::prime_subgroup_generator(); 2], {
+        GroupAffine::prime_subgroup_generator()
+    });
+    assert_eq!(
+        init(elements, 2),
+        Some(IpaError::LengthMismatch {
+            expected: 4,
+            found: 2
+        })
+    );
+
+    let scheme = Scheme::init(Init::Seed(1), 8, false, thread_rng()).unwrap();
+    let too_large = vec![F::one(); 257];
+    assert_eq!(
+        scheme
+            .commit::<Commitment<_, false>>(too_large)
+            .unwrap_err(),
+        IpaError::DegreeTooLarge {
+            degree: 257,
+            max_degree: 256
+        }
+    );
+    let (commit, poly, point, eval) = commit_and_open(&scheme);
+    let mut proof: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    proof.rounds.pop();
+    assert_eq!(
+        scheme.verify(commit, proof).unwrap_err(),
+        IpaError::WrongRoundCount {
+            expected: 8,
+            found: 7
+        }
+    );
+
+    let multi = scheme
+        .batch_open(vec![(commit, &*poly, point, eval)])
+        .unwrap();
+    assert_eq!(
+        scheme
+            .batch_verify(&[commit, commit], multi.clone())
+            .unwrap_err(),
+        IpaError::LengthMismatch {
+            expected: 2,
+            found: 1
+        }
+    );
+    let mut bad_multi = multi;
+    bad_multi.batch_opening.eval += F::one();
+    assert!(scheme.batch_verify(&[commit], bad_multi).is_err());
+    assert_eq!(
+        scheme.batch_open::<false>(vec![]).map(|_| ()).unwrap_err(),
+        IpaError::EmptyBatch
+    );
+}
+
+#[test]
+fn test_short_polynomials() {
+    use ark_poly::{EvaluationDomain, Evaluations, GeneralEvaluationDomain};
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 3, true, thread_rng()).unwrap();
+    let poly = [1, 2, 3, 4, 5].map(F::from).to_vec();
+    let point = F::from(5);
+    let eval =
+        ark_poly::univariate::DensePolynomial::from_coefficients_slice(&poly).evaluate(&point);
+
+    let commit: Commitment<_, false> = scheme.commit(poly.clone()).unwrap();
+    let padded = [poly.clone(), vec![F::from(0); 3]].concat();
+    assert_eq!(commit, scheme.commit(padded).unwrap());
+    let proof: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    assert_eq!(scheme.verify(commit, proof).unwrap(), eval);
+
+    let hiding: UnsafeHidingCommitment<_> = scheme.commit(poly.clone()).unwrap();
+    let proof: HidingOpening<_> = scheme.open(hiding, &poly, point, eval).unwrap();
+    assert_eq!(scheme.verify(hiding.into(), proof).unwrap(), eval);
+
+    let short = [7, 8].map(F::from).to_vec();
+    let short_eval = short[0] + short[1] * point;
+    let short_commit = scheme.commit(short.clone()).unwrap();
+    let multi = scheme
+        .batch_open(vec![
+            (commit, &*poly, point, eval),
+            (short_commit, &*short, point, short_eval),
+        ])
+        .unwrap();
+    let evals = scheme.batch_verify(&[commit, short_commit], multi).unwrap();
+    assert_eq!(evals, vec![eval, short_eval]);
+
+    //evaluations over a smaller domain
+    let domain = GeneralEvaluationDomain::<F>::new(4).unwrap();
+    let coeffs = [1, 2, 3, 4].map(F::from).to_vec();
+    let evals = Evaluations::from_vec_and_domain(domain.fft(&coeffs), domain);
+    let eval_commit: Commitment<_, false> = scheme.commit(evals).unwrap();
+    assert_eq!(eval_commit, scheme.commit(coeffs.clone()).unwrap());
+    let eval =
+        ark_poly::univariate::DensePolynomial::from_coefficients_slice(&coeffs).evaluate(&point);
+    let proof: Opening<_> = scheme.open(eval_commit, &coeffs, point, eval).unwrap();
+    assert_eq!(scheme.verify(eval_commit, proof).unwrap(), eval);
+    let odd = crate::CoeffsOrEvals::Evals(vec![F::one(); 3]);
+    assert_eq!(
+        scheme.commit::<Commitment<_, false>>(odd).unwrap_err(),
+        IpaError::LengthMismatch {
+            expected: 4,
+            found: 3
+        }
+    );
+}
+
+#[test]
+fn test_trim() {
+    use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, true, thread_rng()).unwrap();
+    let trimmed = scheme.trim(3, thread_rng()).unwrap();
+    assert!(scheme.trim(9, thread_rng()).is_err());
+    assert!(scheme.trim(0, thread_rng()).is_err());
+
+    let poly = [1, 2, 3, 4, 5, 6, 7, 8].map(F::from).to_vec();
+    let point = F::from(5);
+    let eval =
+        ark_poly::univariate::DensePolynomial::from_coefficients_slice(&poly).evaluate(&point);
+    let commit: Commitment<_, false> = scheme.commit(poly.clone()).unwrap();
+    assert_eq!(commit, trimmed.commit(poly.clone()).unwrap());
+    let proof: Opening<_> = trimmed.open(commit, &poly, point, eval).unwrap();
+    assert_eq!(proof.rounds.len(), 3);
+    assert_eq!(trimmed.verify(commit, proof.clone()).unwrap(), eval);
+    assert!(scheme.verify(commit, proof).is_err());
+
+    let hiding: UnsafeHidingCommitment<_> = scheme.commit(poly.clone()).unwrap();
+    let proof: HidingOpening<_> = trimmed.open(hiding, &poly, point, eval).unwrap();
+    assert_eq!(trimmed.verify(hiding.into(), proof).unwrap(), eval);
+
+    let evals = crate::CoeffsOrEvals::Evals(
+        ark_poly::univariate::DensePolynomial::from_coefficients_slice(&poly)
+            .evaluate_over_domain(GeneralEvaluationDomain::<F>::new(8).unwrap())
+            .evals,
+    );
+    assert_eq!(commit, trimmed.commit(evals).unwrap());
+}
+
+#[test]
+fn test_verifier_key() {
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, true, thread_rng()).unwrap();
+    let vk = VerifierKey::<PallasParameters>::init(Init::Seed(1), 8).unwrap();
+    let (commit, poly, point, eval) = commit_and_open(&scheme);
+    let proof: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    assert_eq!(vk.verify(commit, proof.clone()).unwrap(), eval);
+    assert_eq!(scheme.verifier_key().verify(commit, proof).unwrap(), eval);
+
+    let hiding: UnsafeHidingCommitment<_> = scheme.commit(poly.clone()).unwrap();
+    let proof: HidingOpening<_> = scheme.open(hiding, &poly, point, eval).unwrap();
+    assert_eq!(vk.verify(hiding.into(), proof).unwrap(), eval);
+
+    let other = VerifierKey::<PallasParameters>::init(Init::Seed(2), 8).unwrap();
+    let proof: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    assert!(other.verify(commit, proof).is_err());
+
+    let trimmed = scheme.trim(3, thread_rng()).unwrap();
+    let poly = [1, 2, 3, 4, 5, 6, 7, 8].map(F::from).to_vec();
+    let eval =
+        ark_poly::univariate::DensePolynomial::from_coefficients_slice(&poly).evaluate(&point);
+    let commit: Commitment<_, false> = trimmed.commit(poly.clone()).unwrap();
+    let proof: Opening<_> = trimmed.open(commit, &poly, point, eval).unwrap();
+    assert_eq!(vk.trim(3).unwrap().verify(commit, proof).unwrap(), eval);
+    assert!(vk.trim(9).is_err());
+    assert!(vk.trim(0).is_err());
+}
+
+#[test]
+fn test_verifier_key_serialization() {
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 8, false, thread_rng()).unwrap();
+    let (commit, poly, point, eval) = commit_and_open(&scheme);
+    let proof: Opening<_> = scheme.open(commit, &poly, point, eval).unwrap();
+    let vk = scheme.verifier_key();
+    for compress in [true, false] {
+        let mut bytes = vec![];
+        let vk = match compress {
+            true => {
+                vk.serialize(&mut bytes).unwrap();
+                assert_eq!(bytes.len(), vk.serialized_size());
+                VerifierKey::<PallasParameters>::deserialize(&*bytes).unwrap()
+            }
+            false => {
+                vk.serialize_uncompressed(&mut bytes).unwrap();
+                assert_eq!(bytes.len(), vk.uncompressed_size());
+                VerifierKey::<PallasParameters>::deserialize_uncompressed(&*bytes).unwrap()
+            }
+        };
+        assert_eq!(vk.verify(commit, proof.clone()).unwrap(), eval);
+    }
+
+    //a basis whose size isn't a power of two, or too small for `init`
+    for size in [3, 1] {
+        let mut bytes = vec![];
+        vk.basis[..size].to_vec().serialize(&mut bytes).unwrap();
+        vk.blinding_basis.serialize(&mut bytes).unwrap();
+        assert!(VerifierKey::<PallasParameters>::deserialize(&*bytes).is_err());
+    }
+}
+
+#[test]
+fn test_shared_scheme() {
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{sync::Arc, thread};
+
+    let scheme =
+        IpaScheme::<PallasParameters, _>::init(Init::Seed(1), 4, false, StdRng::from_entropy())
+            .unwrap();
+    let scheme = Arc::new(scheme);
+    let handles = (0..4)
+        .map(|i| {
+            let scheme = Arc::clone(&scheme);
+            thread::spawn(move || {
+                let poly = (0..16).map(|j| F::from(i * 16 + j)).collect::<Vec<_>>();
+                let point = F::from(3);
+                let eval = ark_poly::univariate::DensePolynomial::from_coefficients_slice(&poly)
+                    .evaluate(&point);
+                let hiding: UnsafeHidingCommitment<_> = scheme.commit(poly.clone()).unwrap();
+                let proof: HidingOpening<_> = scheme.open(hiding, &poly, point, eval).unwrap();
+                scheme.verify(hiding.into(), proof).unwrap() == eval
+            })
+        })
+        .collect::<Vec<_>>();
+    assert!(handles.into_iter().all(|handle| handle.join().unwrap()));
+}
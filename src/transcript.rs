@@ -0,0 +1,180 @@
+use crate::{
+    poseidon::{PoseidonField, PoseidonSponge},
+    Fr,
+};
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, SWModelParameters};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_serialize::CanonicalSerialize;
+use std::marker::PhantomData;
+
+///a Fiat-Shamir transcript over the curve `P`, everything absorbed is bound to
+///every later challenge
+pub trait Transcript<P: SWModelParameters>: Clone {
+    fn new(domain: &'static [u8]) -> Self;
+    fn absorb_point(&mut self, point: &GroupAffine<P>);
+    fn absorb_scalar(&mut self, scalar: &Fr<P>);
+    ///derives a challenge from the whole history, the challenge becomes part of it
+    fn squeeze_challenge(&mut self) -> Fr<P>;
+}
+
+///a transcript working over bytes, usable with any curve
+pub trait ByteTranscript: Clone {
+    fn new(domain: &'static [u8]) -> Self;
+    fn absorb(&mut self, bytes: &[u8]);
+    ///derives 32 bytes from the whole history, the output becomes part of it
+    fn squeeze(&mut self) -> [u8; 32];
+}
+
+impl<P: SWModelParameters, T: ByteTranscript> Transcript<P> for T {
+    fn new(domain: &'static [u8]) -> Self {
+        <T as ByteTranscript>::new(domain)
+    }
+    fn absorb_point(&mut self, point: &GroupAffine<P>) {
+        let mut bytes = vec![];
+        point.serialize_unchecked(&mut bytes).unwrap();
+        self.absorb(&bytes);
+    }
+    fn absorb_scalar(&mut self, scalar: &Fr<P>) {
+        let mut bytes = vec![];
+        scalar.serialize_unchecked(&mut bytes).unwrap();
+        self.absorb(&bytes);
+    }
+    ///two consecutive squeezes are read as a 64 bytes little endian integer and
+    ///reduced modulo the group order, the bias is negligible
+    fn squeeze_challenge(&mut self) -> Fr<P> {
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&self.squeeze());
+        bytes[32..].copy_from_slice(&self.squeeze());
+        <Fr<P>>::from_le_bytes_mod_order(&bytes)
+    }
+}
+
+///a hash function producing 32 bytes digests
+pub trait HashFunction: Clone {
+    fn hash(data: &[u8]) -> [u8; 32];
+}
+
+#[derive(Clone, Debug)]
+pub struct Blake3;
+#[cfg(feature = "sha2")]
+#[derive(Clone, Debug)]
+pub struct Sha256;
+#[cfg(feature = "sha3")]
+#[derive(Clone, Debug)]
+pub struct Keccak256;
+
+impl HashFunction for Blake3 {
+    fn hash(data: &[u8]) -> [u8; 32] {
+        blake3::hash(data).into()
+    }
+}
+#[cfg(feature = "sha2")]
+impl HashFunction for Sha256 {
+    fn hash(data: &[u8]) -> [u8; 32] {
+        use sha2::Digest;
+        sha2::Sha256::digest(data).into()
+    }
+}
+#[cfg(feature = "sha3")]
+impl HashFunction for Keccak256 {
+    fn hash(data: &[u8]) -> [u8; 32] {
+        use sha3::Digest;
+        sha3::Keccak256::digest(data).into()
+    }
+}
+
+///keeps all the absorbed data and hashes it on every squeeze
+#[derive(Clone, Debug)]
+pub struct HashTranscript<H: HashFunction> {
+    data: Vec<u8>,
+    _hash: PhantomData<H>,
+}
+
+pub type Blake3Transcript = HashTranscript<Blake3>;
+#[cfg(feature = "sha2")]
+pub type Sha256Transcript = HashTranscript<Sha256>;
+#[cfg(feature = "sha3")]
+pub type KeccakTranscript = HashTranscript<Keccak256>;
+
+impl<H: HashFunction> ByteTranscript for HashTranscript<H> {
+    fn new(domain: &'static [u8]) -> Self {
+        Self {
+            data: domain.to_vec(),
+            _hash: PhantomData,
+        }
+    }
+    fn absorb(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+    fn squeeze(&mut self) -> [u8; 32] {
+        let output = H::hash(&self.data);
+        self.data.extend_from_slice(&output);
+        output
+    }
+}
+
+///STROBE based transcript, as used by merlin
+#[cfg(feature = "merlin")]
+#[derive(Clone)]
+pub struct MerlinTranscript(merlin::Transcript);
+
+#[cfg(feature = "merlin")]
+impl ByteTranscript for MerlinTranscript {
+    fn new(domain: &'static [u8]) -> Self {
+        Self(merlin::Transcript::new(domain))
+    }
+    fn absorb(&mut self, bytes: &[u8]) {
+        self.0.append_message(b"data", bytes);
+    }
+    fn squeeze(&mut self) -> [u8; 32] {
+        let mut output = [0; 32];
+        self.0.challenge_bytes(b"challenge", &mut output);
+        output
+    }
+}
+
+///Poseidon sponge over the base field of the curve, points are absorbed as
+///their coordinates so the transcript can be replayed cheaply in a circuit over
+///the other curve of the cycle.
+///Scalars are absorbed as two 128 bits limbs and challenges are the low 128 bits
+///of a squeezed base field element.
+#[derive(Clone, Debug)]
+pub struct PoseidonTranscript<F: PoseidonField>(PoseidonSponge<F>);
+
+impl<F: PoseidonField> PoseidonTranscript<F> {
+    fn absorb_limbs(&mut self, bytes: &[u8]) {
+        let limbs = bytes
+            .chunks(16)
+            .map(F::from_le_bytes_mod_order)
+            .collect::<Vec<_>>();
+        self.0.absorb(&limbs);
+    }
+}
+
+impl<P> Transcript<P> for PoseidonTranscript<P::BaseField>
+where
+    P: SWModelParameters,
+    P::BaseField: PoseidonField,
+{
+    fn new(domain: &'static [u8]) -> Self {
+        let mut transcript = Self(PoseidonSponge::new());
+        transcript.absorb_limbs(domain);
+        transcript
+    }
+    fn absorb_point(&mut self, point: &GroupAffine<P>) {
+        //(0, 0) is not on the curve so it can't collide with a real point
+        let coordinates = match point.infinity {
+            true => [P::BaseField::zero(); 2],
+            false => [point.x, point.y],
+        };
+        self.0.absorb(&coordinates);
+    }
+    fn absorb_scalar(&mut self, scalar: &Fr<P>) {
+        self.absorb_limbs(&scalar.into_repr().to_bytes_le());
+    }
+    fn squeeze_challenge(&mut self) -> Fr<P> {
+        let element = self.0.squeeze();
+        let bytes = element.into_repr().to_bytes_le();
+        <Fr<P>>::from_le_bytes_mod_order(&bytes[..16])
+    }
+}
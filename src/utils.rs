@@ -1,58 +1,182 @@
-use crate::Fr;
+use crate::{Fr, IpaError};
 use ark_ec::{
+    msm::VariableBaseMSM,
     short_weierstrass_jacobian::{GroupAffine, GroupProjective},
     AffineCurve, ProjectiveCurve, SWModelParameters,
 };
-use ark_ff::Field;
-use std::ops::Add;
+use ark_ff::{Field, One, PrimeField};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
+///iterates over a reference in parallel with the `parallel` feature
+macro_rules! cfg_iter {
+    ($e:expr) => {{
+        #[cfg(feature = "parallel")]
+        let iter = $e.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = $e.iter();
+        iter
+    }};
+}
+///consumes the collection in parallel with the `parallel` feature
+macro_rules! cfg_into_iter {
+    ($e:expr) => {{
+        #[cfg(feature = "parallel")]
+        let iter = $e.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = $e.into_iter();
+        iter
+    }};
+}
+///iterates over a mutable reference in parallel with the `parallel` feature
+macro_rules! cfg_iter_mut {
+    ($e:expr) => {{
+        #[cfg(feature = "parallel")]
+        let iter = $e.par_iter_mut();
+        #[cfg(not(feature = "parallel"))]
+        let iter = $e.iter_mut();
+        iter
+    }};
+}
+pub(crate) use {cfg_into_iter, cfg_iter, cfg_iter_mut};
+
+///runs both closures, in parallel with the `parallel` feature
+pub(crate) fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(feature = "parallel")]
+    return rayon::join(a, b);
+    #[cfg(not(feature = "parallel"))]
+    return (a(), b());
+}
+
+fn check_len(expected: usize, found: usize) -> Result<(), IpaError> {
+    match expected == found {
+        true => Ok(()),
+        false => Err(IpaError::LengthMismatch { expected, found }),
+    }
+}
+
+///left + right * factor
 pub fn compress_basis<P: SWModelParameters>(
     left: &[GroupAffine<P>],
     right: &[GroupAffine<P>],
-    challenge: Fr<P>,
-) -> Vec<GroupAffine<P>> {
-    assert_eq!(left.len(), right.len());
-    let inverse = challenge.inverse().unwrap();
-    let left = left.iter().map(|elem| elem.mul(inverse));
-    let right = right.iter().map(|elem| elem.mul(challenge));
-    left.zip(right)
-        .map(|(a, b)| (a + b).into_affine())
-        .collect()
+    factor: Fr<P>,
+) -> Result<Vec<GroupAffine<P>>, IpaError> {
+    check_len(left.len(), right.len())?;
+    let folded = cfg_iter!(left)
+        .zip(right)
+        .map(|(a, b)| {
+            let mut point = b.mul(factor);
+            point.add_assign_mixed(a);
+            point
+        })
+        .collect::<Vec<_>>();
+    //a single inversion for all the points
+    Ok(GroupProjective::batch_normalization_into_affine(&folded))
 }
 pub fn compress<P: SWModelParameters>(
     left: &[Fr<P>],
     right: &[Fr<P>],
     challenge: Fr<P>,
     challenge_inverse: Fr<P>,
-) -> Vec<Fr<P>> {
-    assert_eq!(left.len(), right.len());
+) -> Result<Vec<Fr<P>>, IpaError> {
+    check_len(left.len(), right.len())?;
     let inverse = challenge_inverse;
-    let left = left.iter().map(|elem| *elem * inverse);
-    let right = right.iter().map(|elem| *elem * challenge);
-    left.zip(right).map(|(a, b)| a + b).collect()
+    Ok(cfg_iter!(left)
+        .zip(right)
+        .map(|(a, b)| *a * inverse + *b * challenge)
+        .collect())
 }
 
+///as a multi scalar multiplication
 pub fn inner_product<P: SWModelParameters>(
     a: &[GroupAffine<P>],
     b: &[Fr<P>],
-) -> GroupProjective<P> {
-    assert_eq!(a.len(), b.len());
-    a.iter()
-        .zip(b.iter())
-        .map(|(a, b)| a.mul(*b))
-        .reduce(Add::add)
-        .unwrap()
-}
-pub fn scalar_inner_product<P: SWModelParameters>(a: &[Fr<P>], b: &[Fr<P>]) -> Fr<P> {
-    assert_eq!(a.len(), b.len());
-    a.iter()
-        .zip(b.iter())
-        .map(|(a, b)| *a * *b)
-        .reduce(Add::add)
-        .unwrap()
-}
-pub fn split<T>(slice: &[T]) -> (&[T], &[T]) {
+) -> Result<GroupProjective<P>, IpaError> {
+    check_len(a.len(), b.len())?;
+    let scalars = cfg_iter!(b).map(|b| b.into_repr()).collect::<Vec<_>>();
+    Ok(VariableBaseMSM::multi_scalar_mul(a, &scalars))
+}
+pub fn scalar_inner_product<P: SWModelParameters>(
+    a: &[Fr<P>],
+    b: &[Fr<P>],
+) -> Result<Fr<P>, IpaError> {
+    check_len(a.len(), b.len())?;
+    Ok(cfg_iter!(a).zip(b).map(|(a, b)| *a * *b).sum())
+}
+///coefficients of b(X) = prod (inverse_j + challenge_j * X^(2^(k - 1 - j))),
+///the first round has the highest power. The coefficient at `i` is the one at
+///`i` without its highest bit times the square of the challenge of that bit
+pub fn s_vector<P: SWModelParameters>(challenges: &[(Fr<P>, Fr<P>)]) -> Vec<Fr<P>> {
+    let rounds = challenges.len();
+    let squares = challenges
+        .iter()
+        .rev()
+        .map(|(challenge, _)| challenge.square())
+        .collect::<Vec<_>>();
+    let mut s = Vec::with_capacity(1 << rounds);
+    s.push(challenges.iter().map(|(_, inverse)| *inverse).product());
+    for i in 1..1_usize << rounds {
+        let bit = i.ilog2() as usize;
+        s.push(s[i - (1 << bit)] * squares[bit]);
+    }
+    s
+}
+///evaluates b(X) without building it, squaring the point for each round
+pub fn eval_b_poly<P: SWModelParameters>(challenges: &[(Fr<P>, Fr<P>)], point: Fr<P>) -> Fr<P> {
+    let mut power = point;
+    let mut eval = Fr::<P>::one();
+    for (challenge, inverse) in challenges.iter().rev() {
+        eval *= *inverse + *challenge * power;
+        power.square_in_place();
+    }
+    eval
+}
+pub fn split<T>(slice: &[T]) -> Result<(&[T], &[T]), IpaError> {
     let len = slice.len();
-    assert_eq!(len % 2, 0);
-    (&slice[0..len / 2], &slice[len / 2..])
+    check_len(len + len % 2, len)?;
+    Ok((&slice[0..len / 2], &slice[len / 2..]))
+}
+
+#[test]
+fn b_poly() {
+    use ark_ff::UniformRand;
+    use ark_pallas::PallasParameters;
+    use ark_poly::{
+        univariate::{DenseOrSparsePolynomial, DensePolynomial, SparsePolynomial},
+        Polynomial,
+    };
+    use rand::thread_rng;
+
+    type F = Fr<PallasParameters>;
+    let rng = &mut thread_rng();
+    let challenges = (0..5)
+        .map(|_| {
+            let challenge = F::rand(rng);
+            (challenge, challenge.inverse().unwrap())
+        })
+        .collect::<Vec<_>>();
+    let poly = challenges
+        .iter()
+        .enumerate()
+        .map(|(j, (challenge, inverse))| {
+            let exp = 1 << (challenges.len() - 1 - j);
+            SparsePolynomial::from_coefficients_vec(vec![(0, *inverse), (exp, *challenge)])
+        })
+        .fold(
+            SparsePolynomial::from_coefficients_vec(vec![(0, F::one())]),
+            |a, b| a.mul(&b),
+        );
+    let poly: DensePolynomial<F> = DenseOrSparsePolynomial::from(poly).into();
+    assert_eq!(s_vector::<PallasParameters>(&challenges), poly.coeffs);
+    let point = F::rand(rng);
+    assert_eq!(
+        eval_b_poly::<PallasParameters>(&challenges, point),
+        poly.evaluate(&point)
+    );
 }
@@ -1,89 +1,102 @@
-use crate::{challenges::ChallengeGenerator, open::VerifTrait, prove::Commitment, Fr, IpaScheme};
+use crate::{
+    challenges::ChallengeGenerator,
+    open::VerifTrait,
+    prove::Commitment,
+    transcript::Transcript,
+    utils::{eval_b_poly, s_vector},
+    Fr, IpaError, IpaScheme, VerifierKey,
+};
 use ark_ec::{
     short_weierstrass_jacobian::{GroupAffine, GroupProjective},
     AffineCurve, ModelParameters, ProjectiveCurve, SWModelParameters,
 };
 use ark_ff::{Field, One, PrimeField};
-use ark_poly::{
-    univariate::{DenseOrSparsePolynomial, DensePolynomial, SparsePolynomial},
-    Polynomial,
-};
 use rand::Rng;
-use std::ops::Mul;
 
-impl<P, R> IpaScheme<P, R>
+impl<P, R, T> IpaScheme<P, R, T>
 where
     P: ModelParameters + SWModelParameters,
     Fr<P>: One,
     R: Rng,
+    T: Transcript<P>,
 {
-    pub fn verify<O>(&self, commitment: O::Commit, open: O) -> Option<Fr<P>>
+    ///same as verifying with the `VerifierKey` of the scheme
+    pub fn verify<O>(&self, commitment: O::Commit, open: O) -> Result<Fr<P>, IpaError>
     where
-        O: VerifTrait<P, R>,
+        O: VerifTrait<P, T>,
+    {
+        self.vk.verify(commitment, open)
+    }
+}
+
+impl<P, T> VerifierKey<P, T>
+where
+    P: ModelParameters + SWModelParameters,
+    Fr<P>: One,
+    T: Transcript<P>,
+{
+    pub fn verify<O>(&self, commitment: O::Commit, open: O) -> Result<Fr<P>, IpaError>
+    where
+        O: VerifTrait<P, T>,
     {
         open.verify(self, commitment)
     }
 
+    /// compute
+    /// final commitment
+    /// final basis combined with u, as G + b * U
+    /// transcript after all the rounds
+    #[allow(clippy::type_complexity)]
     pub(crate) fn general_verify<const HIDING: bool>(
         &self,
         commitment: Commitment<P, HIDING>,
         point: Fr<P>,
         eval: Fr<P>,
-        a: Fr<P>,
         rounds: Vec<(GroupAffine<P>, GroupAffine<P>)>,
-    ) -> (GroupProjective<P>, GroupProjective<P>) {
-        let u = ChallengeGenerator::inner_product_basis(&commitment, &point);
+    ) -> Result<(GroupProjective<P>, GroupAffine<P>, ChallengeGenerator<P, T>), IpaError> {
+        self.check_round_count(rounds.len())?;
+        let mut transcript = ChallengeGenerator::new_opening(&commitment, &point, &eval);
+        let u = transcript.inner_product_basis();
 
-        let (final_commit, b_poly) = Self::process_rounds(commitment, point, eval, rounds);
-        let b = Self::eval_b_poly(&b_poly, point);
-        let s = Self::sparse_to_dense(b_poly).coeffs;
-        let basis = Self::s_to_basis(&self, s);
+        let (final_commit, challenges) =
+            Self::process_rounds(&commitment, eval, &rounds, u, &mut transcript)?;
+        let b = eval_b_poly::<P>(&challenges, point);
+        let basis = self.s_to_basis(s_vector::<P>(&challenges));
 
-        (final_commit, basis.mul(a) + u.mul(a * b))
+        let final_basis = basis + u.mul(b).into_affine();
+        Ok((final_commit, final_basis, transcript))
     }
-    fn sparse_to_dense(polys: Vec<SparsePolynomial<Fr<P>>>) -> DensePolynomial<Fr<P>> {
-        let poly = polys.into_iter().reduce(|a, b| a.mul(&b)).unwrap();
-        DenseOrSparsePolynomial::from(poly).into()
-    }
-    pub(crate) fn eval_b_poly(b_poly: &Vec<SparsePolynomial<Fr<P>>>, point: Fr<P>) -> Fr<P> {
-        b_poly
-            .iter()
-            .map(|poly| poly.evaluate(&point))
-            .reduce(Mul::mul)
-            .unwrap()
+    ///the basis is halved on each round until a single element is left
+    pub(crate) fn check_round_count(&self, found: usize) -> Result<(), IpaError> {
+        let expected = self.max_degree.trailing_zeros() as usize;
+        match found == expected {
+            true => Ok(()),
+            false => Err(IpaError::WrongRoundCount { expected, found }),
+        }
     }
     /// compute
     /// final commitment
-    /// b_poly
+    /// challenges and their inverses, in round order
+    #[allow(clippy::type_complexity)]
     pub(crate) fn process_rounds<const HIDING: bool>(
-        commitment: Commitment<P, HIDING>,
-        point: Fr<P>,
+        commitment: &Commitment<P, HIDING>,
         eval: Fr<P>,
-        rounds: Vec<(GroupAffine<P>, GroupAffine<P>)>,
-    ) -> (GroupProjective<P>, Vec<SparsePolynomial<Fr<P>>>) {
-        let u = ChallengeGenerator::inner_product_basis(&commitment, &point);
-        let p = commitment.0.into_projective() + u.mul(eval);
-        let mut exp = 2_u64.pow(rounds.len() as u32);
-
-        let b_poly = Vec::with_capacity(rounds.len());
-        let (final_commit, b_poly) = rounds.iter().fold((p, b_poly), |state, (lj, rj)| {
-            let (p, mut b_poly) = state;
-            let challenge = <ChallengeGenerator<P>>::round_challenge(lj, rj);
-            let inverse = challenge.inverse().unwrap();
-            let new_commit = p + (lj.mul(challenge.square()) + rj.mul(inverse.square()));
-
-            exp = exp / 2;
-            let term = SparsePolynomial::from_coefficients_vec(vec![
-                (0, inverse),
-                (exp as usize, challenge),
-            ]);
-            b_poly.push(term);
+        rounds: &[(GroupAffine<P>, GroupAffine<P>)],
+        u: GroupAffine<P>,
+        transcript: &mut ChallengeGenerator<P, T>,
+    ) -> Result<(GroupProjective<P>, Vec<(Fr<P>, Fr<P>)>), IpaError> {
+        let mut p = commitment.0.into_projective() + u.mul(eval);
 
-            (new_commit, b_poly)
-        });
-        (final_commit, b_poly)
+        let mut challenges = Vec::with_capacity(rounds.len());
+        for (lj, rj) in rounds.iter() {
+            let challenge = transcript.round_challenge(lj, rj);
+            let inverse = challenge.inverse().ok_or(IpaError::ZeroChallenge)?;
+            p += lj.mul(challenge.square()) + rj.mul(inverse.square());
+            challenges.push((challenge, inverse));
+        }
+        Ok((p, challenges))
     }
-    fn s_to_basis(&self, s: Vec<Fr<P>>) -> GroupAffine<P> {
+    pub(crate) fn s_to_basis(&self, s: Vec<Fr<P>>) -> GroupAffine<P> {
         debug_assert_eq!(s.len(), self.max_degree);
         let basis = &*self.basis;
         let coeffs = s.into_iter().map(|e| e.into_repr()).collect::<Vec<_>>();